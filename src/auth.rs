@@ -0,0 +1,261 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use p384::{
+    ecdsa::{signature::Verifier, VerifyingKey},
+    PublicKey, SecretKey,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{
+        login::{ChainClaims, ClientData, ConnectionRequest, IdentityData},
+        VarString,
+    },
+    error::{Error, Result},
+    jwt::{self, ALGORITHM, JWT},
+    packet::{
+        encryption::{derive_key, parse_public_key, shared_secret, Encryption},
+        play::S2CHandshake,
+    },
+};
+
+/// This is the base64 encoded public key of the Mojang authentication servers which signs the
+/// certificate chains of the players authenticated with Xbox Live.
+pub const MOJANG_PUBLIC_KEY: &str = "MHYwEAYHKoZIzj0CAQYFK4EEACIDYgAECRXueJeTDqNRRgJi/vlRufByu/2G0i2Ebt6YMar5QX/R0DIIyrJMcUpruK4QveTfJSTp3Shlq4Gk34cD/4GUWwkv0DVuzeuB+tXija7HBxii03NHDbPAD0AKnLr2wdAp";
+
+/// This is the default amount of clock skew allowed between the server and the issuer of the
+/// tokens when checking their expiry.
+pub const DEFAULT_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// This is the maximum number of links in a certificate chain.
+const MAX_CHAIN_LENGTH: usize = 3;
+
+/// Authentication tells whether the certificate chain of a player was signed by the trusted root
+/// key, or by the player themself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authentication {
+    /// The chain was signed by the trusted root key, so the identity of the player can be trusted.
+    Authenticated,
+    /// The chain was only signed by the player, as done by clients in offline mode.
+    SelfSigned,
+}
+
+/// VerifiedLogin is the result of the verification of a connection request.
+#[derive(Debug, Clone)]
+pub struct VerifiedLogin {
+    pub authentication: Authentication,
+    pub identity: IdentityData,
+    pub client_data: ClientData,
+    /// IdentityPublicKey is the public key of the player, used for the key agreement of the
+    /// encryption.
+    pub identity_public_key: PublicKey,
+}
+
+impl VerifiedLogin {
+    /// Returns true if the chain was signed by the trusted root key.
+    pub fn authenticated(&self) -> bool {
+        self.authentication == Authentication::Authenticated
+    }
+}
+
+/// ChainVerifier verifies the ES384 signatures and the expiry of the JWTs in the certificate
+/// chain of a player, anchored at a trusted root key.
+#[derive(Debug, Clone)]
+pub struct ChainVerifier {
+    root_key: PublicKey,
+    clock_skew: Duration,
+}
+
+impl Default for ChainVerifier {
+    fn default() -> Self {
+        Self::new(parse_public_key(MOJANG_PUBLIC_KEY).unwrap())
+    }
+}
+
+impl ChainVerifier {
+    /// Creates and returns a new ChainVerifier trusting the provided root key.
+    pub fn new(root_key: PublicKey) -> Self {
+        Self {
+            root_key,
+            clock_skew: DEFAULT_CLOCK_SKEW,
+        }
+    }
+
+    /// Sets the amount of clock skew allowed when checking the expiry of the tokens.
+    pub fn with_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    /// Verifies the connection request at the current time.
+    pub fn verify(&self, request: &ConnectionRequest) -> Result<VerifiedLogin> {
+        self.verify_at(request, SystemTime::now())
+    }
+
+    /// Verifies the connection request at the provided time. Each link of the chain must be signed
+    /// by the `identityPublicKey` of the previous link, the first link being signed by the key in
+    /// its own `x5u` header. The client data must be signed by the key of the last link.
+    pub fn verify_at(&self, request: &ConnectionRequest, now: SystemTime) -> Result<VerifiedLogin> {
+        let chain = &request.chain.chain;
+        if chain.is_empty() || chain.len() > MAX_CHAIN_LENGTH {
+            return Err(Error::InvalidJWT(format!(
+                "chain has {} links, expected 1 to {}",
+                chain.len(),
+                MAX_CHAIN_LENGTH
+            )));
+        }
+
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| Error::InvalidJWT(err.to_string()))?
+            .as_secs() as i64;
+
+        let mut authentication = Authentication::SelfSigned;
+        let mut expected_key: Option<PublicKey> = None;
+        let mut identity = IdentityData::default();
+
+        for token in chain {
+            let jwt = JWT::decode(token)?;
+            let signer = self.signer(&jwt)?;
+
+            if let Some(expected) = expected_key {
+                if expected != signer {
+                    return Err(Error::InvalidJWT(
+                        "link is not signed by the key of the previous link".into(),
+                    ));
+                }
+            }
+
+            verify_signature(&jwt, &signer)?;
+
+            if signer == self.root_key {
+                authentication = Authentication::Authenticated;
+            }
+
+            let claims: ChainClaims = jwt.claims()?;
+            self.check_time(&claims, now)?;
+
+            expected_key = Some(parse_public_key(&claims.identity_public_key)?);
+            identity = claims.extra_data;
+        }
+
+        let identity_public_key = expected_key.unwrap();
+
+        let client_data = JWT::decode(&request.client_data)?;
+        verify_signature(&client_data, &identity_public_key)?;
+
+        Ok(VerifiedLogin {
+            authentication,
+            identity,
+            client_data: client_data.claims()?,
+            identity_public_key,
+        })
+    }
+
+    /// Returns the public key in the `x5u` header of the token.
+    fn signer(&self, jwt: &JWT) -> Result<PublicKey> {
+        if jwt.header.alg != ALGORITHM {
+            return Err(Error::InvalidJWT(format!(
+                "unsupported algorithm {}",
+                jwt.header.alg
+            )));
+        }
+
+        let x5u = jwt
+            .header
+            .x5u
+            .as_ref()
+            .ok_or_else(|| Error::InvalidJWT("missing x5u header".into()))?;
+
+        parse_public_key(x5u)
+    }
+
+    /// Checks that the token is neither expired nor used before it is valid, allowing for the clock
+    /// skew.
+    fn check_time(&self, claims: &ChainClaims, now: i64) -> Result<()> {
+        let skew = self.clock_skew.as_secs() as i64;
+
+        if let Some(expires_at) = claims.expires_at {
+            if now > expires_at + skew {
+                return Err(Error::InvalidJWT("token has expired".into()));
+            }
+        }
+
+        if let Some(not_before) = claims.not_before {
+            if now + skew < not_before {
+                return Err(Error::InvalidJWT("token is not valid yet".into()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn verify_signature(jwt: &JWT, key: &PublicKey) -> Result<()> {
+    VerifyingKey::from(key)
+        .verify(jwt.signed.as_bytes(), &jwt.signature()?)
+        .map_err(|_| Error::InvalidJWT("signature does not match".into()))
+}
+
+/// HandshakeClaims are the claims of the JWT sent in the S2CHandshake packet.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HandshakeClaims {
+    /// Salt is the base64 encoded salt used to derive the key of the encryption.
+    pub salt: String,
+}
+
+/// ServerHandshake is the S2CHandshake packet built by the server along with the secrets derived
+/// from the key agreement with the client.
+pub struct ServerHandshake {
+    pub packet: S2CHandshake,
+    /// SharedSecret is the ECDH shared secret between the server and the client.
+    pub shared_secret: [u8; 48],
+    /// Key is the key of the encryption derived from the salt and the shared secret.
+    pub key: [u8; 32],
+}
+
+impl ServerHandshake {
+    /// Builds the S2CHandshake packet signed by the key pair of the server and derives the
+    /// secrets shared with the client whose public key was verified from the Login packet.
+    pub fn new(server_key: &SecretKey, client_key: &PublicKey, salt: &[u8]) -> Result<Self> {
+        let claims = HandshakeClaims {
+            salt: STANDARD.encode(salt),
+        };
+
+        let shared_secret = shared_secret(server_key, client_key);
+        Ok(Self {
+            packet: S2CHandshake {
+                jwt: VarString::new(jwt::sign(&claims, server_key)?),
+            },
+            shared_secret,
+            key: derive_key(salt, &shared_secret),
+        })
+    }
+
+    /// Returns the encryption to be enabled once the S2CHandshake packet has been sent.
+    pub fn encryption(&self) -> Encryption {
+        Encryption::new(self.key)
+    }
+}
+
+/// Verifies the S2CHandshake packet received by the client and returns the encryption to be enabled
+/// before sending the C2SHandshake packet.
+pub fn client_handshake(packet: &S2CHandshake, client_key: &SecretKey) -> Result<Encryption> {
+    let jwt = JWT::decode(&packet.jwt.0)?;
+    let x5u = jwt
+        .header
+        .x5u
+        .as_ref()
+        .ok_or_else(|| Error::InvalidJWT("missing x5u header".into()))?;
+
+    let server_key = parse_public_key(x5u)?;
+    verify_signature(&jwt, &server_key)?;
+
+    let claims: HandshakeClaims = jwt.claims()?;
+    let salt = STANDARD
+        .decode(&claims.salt)
+        .map_err(|err| Error::InvalidJWT(err.to_string()))?;
+
+    Ok(Encryption::from_keys(client_key, &server_key, &salt))
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, VecDeque};
+
+use xxhash_rust::xxh64::xxh64;
+
+use crate::{
+    data::chunk::CacheBlob,
+    packet::play::{ClientCacheBlobStatus, ClientCacheMissResponse},
+};
+
+/// Returns the hash of a blob, which is the xxHash64 of its bytes with a seed of zero.
+pub fn blob_hash(blob: &[u8]) -> u64 {
+    xxh64(blob, 0)
+}
+
+/// The maximum number of blobs a BlobCache keeps for a client that has not reported them yet.
+pub const MAX_PENDING: usize = 4096;
+
+/// BlobCache keeps track of the blob cache of a single client. The sub chunks and biomes sent to a
+/// client that has enabled the cache are replaced with the hashes of their blobs, which the client
+/// then reports as hits or misses in a ClientCacheBlobStatus packet. The blobs are kept until the
+/// client reports them, so that the misses can be answered with a ClientCacheMissResponse packet.
+/// At most [`MAX_PENDING`] blobs are kept, and the oldest are evicted first.
+#[derive(Debug, Default)]
+pub struct BlobCache {
+    enabled: bool,
+    pending: HashMap<u64, Vec<u8>>,
+    order: VecDeque<u64>,
+}
+
+impl BlobCache {
+    /// Creates and returns a new BlobCache for a client that has not enabled the cache yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the client has enabled the blob cache.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Sets whether the client has enabled the blob cache, as sent in the ClientCacheStatus packet.
+    /// The blobs are forgotten if the cache is disabled.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        if !enabled {
+            self.pending.clear();
+            self.order.clear();
+        }
+    }
+
+    /// Adds the blob to the blobs the client has not reported yet and returns its hash. The blob
+    /// is kept even if the client has reported it as a hit before, as the client may have evicted
+    /// it since. The oldest blob is evicted if more than [`MAX_PENDING`] blobs are kept.
+    pub fn insert(&mut self, blob: Vec<u8>) -> u64 {
+        let hash = blob_hash(&blob);
+        if self.pending.insert(hash, blob).is_none() {
+            self.order.push_back(hash);
+        }
+
+        while self.pending.len() > MAX_PENDING {
+            match self.order.pop_front() {
+                Some(oldest) => self.pending.remove(&oldest),
+                None => break,
+            };
+        }
+
+        hash
+    }
+
+    /// Returns the number of blobs the client has not reported yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Handles the hits and misses reported by the client. The blobs reported as misses are
+    /// returned in a ClientCacheMissResponse packet, unless there are none. Hashes that were never
+    /// sent to the client, or whose blobs have been evicted, are ignored.
+    pub fn handle_blob_status(
+        &mut self,
+        status: &ClientCacheBlobStatus,
+    ) -> Option<ClientCacheMissResponse> {
+        for hash in &status.hit_hashes {
+            self.pending.remove(hash);
+        }
+
+        let mut blobs = Vec::new();
+        for hash in &status.miss_hashes {
+            if let Some(payload) = self.pending.remove(hash) {
+                blobs.push(CacheBlob {
+                    hash: *hash,
+                    payload,
+                });
+            }
+        }
+
+        let pending = &self.pending;
+        self.order.retain(|hash| pending.contains_key(hash));
+
+        if blobs.is_empty() {
+            return None;
+        }
+
+        Some(ClientCacheMissResponse {
+            blobs: blobs.into(),
+        })
+    }
+}
@@ -0,0 +1,776 @@
+use std::io::{self, Read, Write};
+
+use binary::{
+    binary::Binary,
+    datatypes::{VarI32, VarU32, U64, U8},
+};
+use binary_derive::Binary;
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use bytes::Buf;
+
+use crate::{
+    binary_enum,
+    cache::BlobCache,
+    error::{Error, Result},
+};
+
+/// This is the number of blocks in a sub chunk, which is a cube of 16 blocks.
+pub const SUB_CHUNK_VOLUME: usize = 4096;
+
+/// This is the number of vertical sections of the overworld, from Y -64 to Y 320.
+pub const OVERWORLD_SECTIONS: usize = 24;
+
+/// This is the number of vertical sections of the nether, from Y 0 to Y 128.
+pub const NETHER_SECTIONS: usize = 8;
+
+/// This is the number of vertical sections of the end, from Y 0 to Y 256.
+pub const END_SECTIONS: usize = 16;
+
+/// These are the numbers of bits per entry a paletted storage may be encoded with.
+const VALID_BITS_PER_ENTRY: [u8; 9] = [0, 1, 2, 3, 4, 5, 6, 8, 16];
+
+/// This is the number of bits per entry written in the header of a biome storage that is the same
+/// as the storage of the section below it.
+const COPY_PREVIOUS_BITS: u8 = 127;
+
+/// Returns the index of the block at the coordinates relative to the sub chunk, in the XZY order
+/// used by the paletted storages.
+pub fn block_index(x: u8, y: u8, z: u8) -> usize {
+    ((x as usize & 15) << 8) | ((z as usize & 15) << 4) | (y as usize & 15)
+}
+
+/// PalettedStorage holds a value for each of the 4096 entries of a sub chunk as an index into a
+/// palette of the distinct values, which is how both the blocks and the biomes are encoded. The
+/// values are runtime IDs for blocks and biome IDs for biomes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalettedStorage {
+    palette: Vec<u32>,
+    indices: Vec<u16>,
+}
+
+impl PalettedStorage {
+    /// Creates and returns a new storage in which every entry is the value.
+    pub fn new(value: u32) -> Self {
+        Self {
+            palette: vec![value],
+            indices: vec![0; SUB_CHUNK_VOLUME],
+        }
+    }
+
+    /// Creates and returns a new storage from the values of the entries, indexed as returned by
+    /// [`block_index`].
+    pub fn from_values(values: &[u32]) -> Result<Self> {
+        if values.len() != SUB_CHUNK_VOLUME {
+            return Err(Error::InvalidChunk(format!(
+                "expected {} values, found {}",
+                SUB_CHUNK_VOLUME,
+                values.len()
+            )));
+        }
+
+        let mut storage = Self {
+            palette: Vec::new(),
+            indices: Vec::with_capacity(SUB_CHUNK_VOLUME),
+        };
+
+        for value in values {
+            let index = storage.palette_index(*value);
+            storage.indices.push(index);
+        }
+
+        Ok(storage)
+    }
+
+    /// Returns the values of all the entries, indexed as returned by [`block_index`].
+    pub fn values(&self) -> Vec<u32> {
+        self.indices
+            .iter()
+            .map(|index| self.palette[*index as usize])
+            .collect()
+    }
+
+    /// Returns the palette of the distinct values of the storage.
+    pub fn palette(&self) -> &[u32] {
+        &self.palette
+    }
+
+    /// Returns the value of the entry at the coordinates relative to the sub chunk.
+    pub fn get(&self, x: u8, y: u8, z: u8) -> u32 {
+        self.palette[self.indices[block_index(x, y, z)] as usize]
+    }
+
+    /// Sets the value of the entry at the coordinates relative to the sub chunk.
+    pub fn set(&mut self, x: u8, y: u8, z: u8, value: u32) {
+        let index = self.palette_index(value);
+        self.indices[block_index(x, y, z)] = index;
+    }
+
+    /// Returns the index of the value in the palette, adding it if it is not there yet.
+    fn palette_index(&mut self, value: u32) -> u16 {
+        match self.palette.iter().position(|v| *v == value) {
+            Some(index) => index as u16,
+            None => {
+                if self.palette.len() >= SUB_CHUNK_VOLUME {
+                    self.compact();
+                }
+
+                self.palette.push(value);
+                (self.palette.len() - 1) as u16
+            }
+        }
+    }
+
+    /// Removes the values of the palette that are no longer used by any entry.
+    pub fn compact(&mut self) {
+        let values = self.values();
+        self.palette.clear();
+        self.indices.clear();
+
+        for value in values {
+            let index = self.palette_index(value);
+            self.indices.push(index);
+        }
+    }
+
+    /// Returns the smallest number of bits per entry able to index the palette.
+    pub fn bits_per_entry(&self) -> u8 {
+        VALID_BITS_PER_ENTRY
+            .into_iter()
+            .find(|bits| (1usize << bits) >= self.palette.len())
+            .unwrap_or(16)
+    }
+
+    /// Encodes the storage in the network format, in which the palette holds runtime IDs.
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        let bits = self.bits_per_entry();
+        buf.push((bits << 1) | 1);
+
+        if bits == 0 {
+            VarI32::new(self.palette[0] as i32).serialize(buf);
+            return;
+        }
+
+        let per_word = 32 / bits as usize;
+        for chunk in self.indices.chunks(per_word) {
+            let mut word = 0u32;
+            for (i, index) in chunk.iter().enumerate() {
+                word |= (*index as u32) << (i * bits as usize);
+            }
+            buf.write_u32::<LE>(word).unwrap();
+        }
+
+        VarI32::new(self.palette.len() as i32).serialize(buf);
+        for value in &self.palette {
+            VarI32::new(*value as i32).serialize(buf);
+        }
+    }
+
+    /// Decodes a storage in the network format. None is returned if the header asks for a copy of
+    /// the previous storage, which is only done for biomes.
+    pub fn read(buf: &mut &[u8]) -> Result<Option<Self>> {
+        let header = buf.read_u8()?;
+        let bits = header >> 1;
+
+        if bits == COPY_PREVIOUS_BITS {
+            return Ok(None);
+        }
+        if header & 1 == 0 {
+            return Err(Error::InvalidChunk(
+                "storages with a persistent palette are not supported".into(),
+            ));
+        }
+        if !VALID_BITS_PER_ENTRY.contains(&bits) {
+            return Err(Error::InvalidChunk(format!(
+                "invalid bits per entry {}",
+                bits
+            )));
+        }
+
+        if bits == 0 {
+            let value = VarI32::deserialize(buf)?.0 as u32;
+            return Ok(Some(Self::new(value)));
+        }
+
+        let per_word = 32 / bits as usize;
+        let mask = (1u32 << bits) - 1;
+        let words = (SUB_CHUNK_VOLUME + per_word - 1) / per_word;
+
+        let mut indices = Vec::with_capacity(SUB_CHUNK_VOLUME);
+        for _ in 0..words {
+            let word = buf.read_u32::<LE>()?;
+            for i in 0..per_word {
+                if indices.len() == SUB_CHUNK_VOLUME {
+                    break;
+                }
+                indices.push(((word >> (i * bits as usize)) & mask) as u16);
+            }
+        }
+
+        let len = VarI32::deserialize(buf)?.0;
+        if len <= 0 || len as usize > SUB_CHUNK_VOLUME {
+            return Err(Error::InvalidChunk(format!("invalid palette size {}", len)));
+        }
+
+        let mut palette = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            palette.push(VarI32::deserialize(buf)?.0 as u32);
+        }
+
+        if let Some(index) = indices.iter().find(|i| **i as usize >= palette.len()) {
+            return Err(Error::InvalidChunk(format!(
+                "palette index {} out of bounds for palette of size {}",
+                index,
+                palette.len()
+            )));
+        }
+
+        Ok(Some(Self { palette, indices }))
+    }
+}
+
+/// SubChunkVersion is the version of the encoding of a sub chunk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SubChunkVersion {
+    /// Version 8 supports multiple block layers.
+    V8,
+    /// Version 9 additionally carries the vertical index of the sub chunk.
+    #[default]
+    V9,
+}
+
+/// SubChunkData is a 16x16x16 section of a chunk. The blocks are stored in layers, where the first
+/// layer holds the regular blocks and the second one usually holds the water of waterlogged
+/// blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubChunkData {
+    pub version: SubChunkVersion,
+    /// Index is the vertical index of the sub chunk, which is negative below Y 0.
+    pub index: i8,
+    pub layers: Vec<PalettedStorage>,
+}
+
+impl SubChunkData {
+    /// Creates and returns a new sub chunk filled with the block.
+    pub fn new(index: i8, block: u32) -> Self {
+        Self {
+            version: SubChunkVersion::default(),
+            index,
+            layers: vec![PalettedStorage::new(block)],
+        }
+    }
+
+    /// Creates and returns a new sub chunk from the runtime IDs of the blocks of each layer,
+    /// indexed as returned by [`block_index`].
+    pub fn from_blocks(index: i8, layers: &[&[u32]]) -> Result<Self> {
+        let layers = layers
+            .iter()
+            .map(|blocks| PalettedStorage::from_values(blocks))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            version: SubChunkVersion::default(),
+            index,
+            layers,
+        })
+    }
+
+    /// Returns the runtime IDs of the blocks of the layer, indexed as returned by [`block_index`].
+    pub fn blocks(&self, layer: usize) -> Option<Vec<u32>> {
+        self.layers.get(layer).map(PalettedStorage::values)
+    }
+
+    /// Returns the runtime ID of the block of the layer at the coordinates relative to the sub
+    /// chunk, if the layer exists.
+    pub fn block(&self, x: u8, y: u8, z: u8, layer: usize) -> Option<u32> {
+        self.layers.get(layer).map(|storage| storage.get(x, y, z))
+    }
+
+    /// Sets the runtime ID of the block of the layer at the coordinates relative to the sub chunk.
+    /// The missing layers below the layer are filled with the air block.
+    pub fn set_block(&mut self, x: u8, y: u8, z: u8, layer: usize, block: u32, air: u32) {
+        while self.layers.len() <= layer {
+            self.layers.push(PalettedStorage::new(air));
+        }
+
+        self.layers[layer].set(x, y, z, block);
+    }
+
+    /// Encodes the sub chunk in the network format.
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        match self.version {
+            SubChunkVersion::V8 => {
+                buf.push(8);
+                buf.push(self.layers.len() as u8);
+            }
+            SubChunkVersion::V9 => {
+                buf.push(9);
+                buf.push(self.layers.len() as u8);
+                buf.push(self.index as u8);
+            }
+        }
+
+        for layer in &self.layers {
+            layer.write(buf);
+        }
+    }
+
+    /// Decodes a sub chunk in the network format. The index is used for version 8 sub chunks,
+    /// which do not carry it.
+    pub fn read(buf: &mut &[u8], index: i8) -> Result<Self> {
+        let (version, count, index) = match buf.read_u8()? {
+            8 => (SubChunkVersion::V8, buf.read_u8()?, index),
+            9 => (SubChunkVersion::V9, buf.read_u8()?, buf.read_i8()?),
+            version => {
+                return Err(Error::InvalidChunk(format!(
+                    "unsupported sub chunk version {}",
+                    version
+                )))
+            }
+        };
+
+        let mut layers = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let layer = PalettedStorage::read(buf)?.ok_or_else(|| {
+                Error::InvalidChunk("block storages may not copy the previous storage".into())
+            })?;
+            layers.push(layer);
+        }
+
+        Ok(Self {
+            version,
+            index,
+            layers,
+        })
+    }
+}
+
+/// ChunkData is the content of the payload of the LevelChunk packet: the sub chunks from the
+/// bottom of the dimension, the biomes of every section of the dimension, the border blocks and
+/// the NBT of the block entities.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkData {
+    pub sub_chunks: Vec<SubChunkData>,
+    pub biomes: Vec<PalettedStorage>,
+    pub border_blocks: Vec<u8>,
+    /// BlockEntities is the network little endian NBT of the block entities, one compound after
+    /// the other.
+    pub block_entities: Vec<u8>,
+}
+
+impl ChunkData {
+    /// Encodes the chunk data, failing if there are more border blocks than their length prefix
+    /// can hold.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+
+        for sub_chunk in &self.sub_chunks {
+            sub_chunk.write(&mut buf);
+        }
+
+        self.write_biomes(&mut buf);
+        self.write_trailer(&mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Encodes the chunk data for a client that has enabled the blob cache. The blobs of the sub
+    /// chunks are returned followed by the blob of the biomes, along with the payload, which only
+    /// holds the border blocks and the block entities.
+    pub fn encode_cached(&self) -> Result<(Vec<Vec<u8>>, Vec<u8>)> {
+        let mut blobs = Vec::with_capacity(self.sub_chunks.len() + 1);
+        for sub_chunk in &self.sub_chunks {
+            let mut blob = Vec::new();
+            sub_chunk.write(&mut blob);
+            blobs.push(blob);
+        }
+
+        let mut blob = Vec::new();
+        self.write_biomes(&mut blob);
+        blobs.push(blob);
+
+        let mut payload = Vec::new();
+        self.write_trailer(&mut payload)?;
+
+        Ok((blobs, payload))
+    }
+
+    /// Encodes the biome storages. The storages that are equal to the one of the section below
+    /// them are encoded as a copy of it.
+    fn write_biomes(&self, buf: &mut Vec<u8>) {
+        let mut previous: Option<&PalettedStorage> = None;
+        for biomes in &self.biomes {
+            if previous == Some(biomes) {
+                buf.push((COPY_PREVIOUS_BITS << 1) | 1);
+            } else {
+                biomes.write(buf);
+            }
+            previous = Some(biomes);
+        }
+    }
+
+    /// Encodes the border blocks and the block entities, which are never cached. The border blocks
+    /// are prefixed with their length as a single byte.
+    fn write_trailer(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let len = u8::try_from(self.border_blocks.len()).map_err(|_| Error::OversizePrefix {
+            len: self.border_blocks.len(),
+            max: u8::MAX as usize,
+        })?;
+
+        buf.push(len);
+        buf.extend_from_slice(&self.border_blocks);
+        buf.extend_from_slice(&self.block_entities);
+
+        Ok(())
+    }
+
+    /// Decodes the chunk data of a chunk with the number of sub chunks and of biome sections,
+    /// which depends on the dimension of the chunk. The index of the lowest sub chunk is used
+    /// for the sub chunks that do not carry their index.
+    pub fn decode(
+        data: &[u8],
+        sub_chunk_count: usize,
+        biome_sections: usize,
+        lowest_index: i8,
+    ) -> Result<Self> {
+        let mut buf = data;
+
+        let mut sub_chunks = Vec::with_capacity(sub_chunk_count.min(biome_sections));
+        for i in 0..sub_chunk_count {
+            let index = lowest_index.wrapping_add(i as i8);
+            sub_chunks.push(SubChunkData::read(&mut buf, index)?);
+        }
+
+        let mut biomes: Vec<PalettedStorage> = Vec::with_capacity(biome_sections);
+        for _ in 0..biome_sections {
+            let storage = match PalettedStorage::read(&mut buf)? {
+                Some(storage) => storage,
+                None => biomes.last().cloned().ok_or_else(|| {
+                    Error::InvalidChunk("the first biome storage may not copy another".into())
+                })?,
+            };
+            biomes.push(storage);
+        }
+
+        let len = buf.read_u8()? as usize;
+        if len > buf.len() {
+            return Err(Error::OversizePrefix {
+                len,
+                max: buf.len(),
+            });
+        }
+
+        let (border_blocks, block_entities) = buf.split_at(len);
+
+        Ok(Self {
+            sub_chunks,
+            biomes,
+            border_blocks: border_blocks.to_vec(),
+            block_entities: block_entities.to_vec(),
+        })
+    }
+}
+
+/// SubChunkRequestMode decides how the client obtains the sub chunks of a chunk sent in the
+/// LevelChunk packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubChunkRequestMode {
+    /// The payload of the LevelChunk packet holds the number of sub chunks.
+    Legacy(u32),
+    /// The client requests every sub chunk of the chunk using the SubChunkRequest packet.
+    Limitless,
+    /// The client requests the sub chunks up to the highest sub chunk using the SubChunkRequest
+    /// packet.
+    Limited { highest_sub_chunk: u16 },
+}
+
+impl Default for SubChunkRequestMode {
+    fn default() -> Self {
+        Self::Legacy(0)
+    }
+}
+
+impl SubChunkRequestMode {
+    /// This is the sub chunk count sent for the limitless request mode.
+    const LIMITLESS: u32 = u32::MAX;
+
+    /// This is the sub chunk count sent for the limited request mode.
+    const LIMITED: u32 = u32::MAX - 1;
+
+    /// Returns the sub chunk count encoded for the mode.
+    pub fn sub_chunk_count(&self) -> u32 {
+        match self {
+            Self::Legacy(count) => *count,
+            Self::Limitless => Self::LIMITLESS,
+            Self::Limited { .. } => Self::LIMITED,
+        }
+    }
+}
+
+/// SubChunkPos is the position of a sub chunk, in sub chunk coordinates.
+#[derive(Default, Debug, Clone, Binary)]
+pub struct SubChunkPos {
+    pub x: VarI32,
+    pub y: VarI32,
+    pub z: VarI32,
+}
+
+/// SubChunkOffset is the offset of a sub chunk from the position of a SubChunkRequest packet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubChunkOffset {
+    pub x: i8,
+    pub y: i8,
+    pub z: i8,
+}
+
+impl Binary for SubChunkOffset {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        buf.write_all(&[self.x as u8, self.y as u8, self.z as u8])
+            .unwrap();
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> io::Result<Self> {
+        let mut offset = [0u8; 3];
+        buf.read_exact(&mut offset)?;
+
+        Ok(Self {
+            x: offset[0] as i8,
+            y: offset[1] as i8,
+            z: offset[2] as i8,
+        })
+    }
+}
+
+binary_enum! {
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub enum SubChunkResult: U8 {
+        #[default]
+        Success = 1,
+        ChunkNotFound,
+        InvalidDimension,
+        PlayerNotFound,
+        IndexOutOfBounds,
+        /// The sub chunk only holds air, so its payload is omitted when the blob cache is enabled.
+        SuccessAllAir,
+    }
+}
+
+/// HeightMap holds the highest block of each column of a sub chunk, relative to the bottom of the
+/// sub chunk. The variants without data tell the client that every column is above or below the
+/// sub chunk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HeightMap {
+    #[default]
+    NoData,
+    HasData(Box<[i8; 256]>),
+    AllTooHigh,
+    AllTooLow,
+}
+
+impl Binary for HeightMap {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        match self {
+            Self::NoData => buf.write_all(&[0]).unwrap(),
+            Self::HasData(data) => {
+                buf.write_all(&[1]).unwrap();
+                buf.write_all(&data.map(|height| height as u8)).unwrap();
+            }
+            Self::AllTooHigh => buf.write_all(&[2]).unwrap(),
+            Self::AllTooLow => buf.write_all(&[3]).unwrap(),
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> io::Result<Self> {
+        let height_map = match buf.read_u8()? {
+            0 => Self::NoData,
+            1 => {
+                let mut data = [0u8; 256];
+                buf.read_exact(&mut data)?;
+                Self::HasData(Box::new(data.map(|height| height as i8)))
+            }
+            2 => Self::AllTooHigh,
+            3 => Self::AllTooLow,
+            value => {
+                let err = Error::InvalidDiscriminant {
+                    ty: "HeightMap",
+                    value: value as i64,
+                };
+                return Err(err.into());
+            }
+        };
+
+        Ok(height_map)
+    }
+}
+
+/// SubChunkEntry is the answer to the request of a single sub chunk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubChunkEntry {
+    pub offset: SubChunkOffset,
+    pub result: SubChunkResult,
+    /// RawPayload holds the encoded sub chunk followed by the NBT of its block entities.
+    pub raw_payload: Vec<u8>,
+    pub height_map: HeightMap,
+    /// BlobHash is the hash of the sub chunk, only sent if the blob cache is enabled.
+    pub blob_hash: u64,
+}
+
+impl SubChunkEntry {
+    /// Creates and returns a new successful entry holding the sub chunk and the network little
+    /// endian NBT of its block entities.
+    pub fn success(
+        offset: SubChunkOffset,
+        sub_chunk: &SubChunkData,
+        block_entities: &[u8],
+        height_map: HeightMap,
+    ) -> Self {
+        let mut raw_payload = Vec::new();
+        sub_chunk.write(&mut raw_payload);
+        raw_payload.extend_from_slice(block_entities);
+
+        Self {
+            offset,
+            result: SubChunkResult::Success,
+            raw_payload,
+            height_map,
+            blob_hash: 0,
+        }
+    }
+
+    /// Creates and returns a new successful entry for a client that has enabled the blob cache.
+    /// The sub chunk is added to the cache as a blob, so the payload only holds the NBT of the
+    /// block entities.
+    pub fn cached(
+        offset: SubChunkOffset,
+        sub_chunk: &SubChunkData,
+        block_entities: &[u8],
+        height_map: HeightMap,
+        cache: &mut BlobCache,
+    ) -> Self {
+        let mut blob = Vec::new();
+        sub_chunk.write(&mut blob);
+
+        Self {
+            offset,
+            result: SubChunkResult::Success,
+            raw_payload: block_entities.to_vec(),
+            height_map,
+            blob_hash: cache.insert(blob),
+        }
+    }
+
+    /// Creates and returns a new successful entry for a sub chunk that only holds air.
+    pub fn all_air(offset: SubChunkOffset, air: u32, index: i8) -> Self {
+        let mut entry = Self::success(
+            offset,
+            &SubChunkData::new(index, air),
+            &[],
+            HeightMap::AllTooLow,
+        );
+        entry.result = SubChunkResult::SuccessAllAir;
+        entry
+    }
+
+    /// Creates and returns a new entry reporting that the sub chunk could not be sent.
+    pub fn failure(offset: SubChunkOffset, result: SubChunkResult) -> Self {
+        Self {
+            offset,
+            result,
+            ..Default::default()
+        }
+    }
+
+    /// Decodes the sub chunk of the payload, returning it along with the NBT of its block
+    /// entities.
+    pub fn sub_chunk(&self, index: i8) -> Result<(SubChunkData, &[u8])> {
+        let mut buf = self.raw_payload.as_slice();
+        let sub_chunk = SubChunkData::read(&mut buf, index)?;
+
+        Ok((sub_chunk, buf))
+    }
+
+    /// Encodes the entry. The payload of sub chunks that only hold air and the blob hash depend
+    /// on whether the blob cache is enabled.
+    pub fn write<W: Write>(&self, buf: &mut W, cache_enabled: bool) {
+        self.offset.serialize(buf);
+        self.result.serialize(buf);
+
+        if !cache_enabled || self.result != SubChunkResult::SuccessAllAir {
+            VarU32::new(self.raw_payload.len() as u32).serialize(buf);
+            buf.write_all(&self.raw_payload).unwrap();
+        }
+
+        self.height_map.serialize(buf);
+
+        if cache_enabled {
+            U64::<LE>::new(self.blob_hash).serialize(buf);
+        }
+    }
+
+    /// Decodes an entry.
+    pub fn read<R: Read + Buf>(buf: &mut R, cache_enabled: bool) -> Result<Self> {
+        let offset = SubChunkOffset::deserialize(buf)?;
+        let result = SubChunkResult::deserialize(buf)?;
+
+        let mut raw_payload = Vec::new();
+        if !cache_enabled || result != SubChunkResult::SuccessAllAir {
+            let len = VarU32::deserialize(buf)?.0 as usize;
+            if len > buf.remaining() {
+                return Err(Error::OversizePrefix {
+                    len,
+                    max: buf.remaining(),
+                });
+            }
+
+            raw_payload = vec![0u8; len];
+            buf.read_exact(&mut raw_payload)?;
+        }
+
+        let height_map = HeightMap::deserialize(buf)?;
+
+        let mut blob_hash = 0;
+        if cache_enabled {
+            blob_hash = U64::<LE>::deserialize(buf)?.0;
+        }
+
+        Ok(Self {
+            offset,
+            result,
+            raw_payload,
+            height_map,
+            blob_hash,
+        })
+    }
+}
+
+/// CacheBlob is a blob sent to a client that reported it missing from its cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheBlob {
+    pub hash: u64,
+    pub payload: Vec<u8>,
+}
+
+impl Binary for CacheBlob {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        U64::<LE>::new(self.hash).serialize(buf);
+        VarU32::new(self.payload.len() as u32).serialize(buf);
+        buf.write_all(&self.payload).unwrap();
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> io::Result<Self> {
+        let hash = U64::<LE>::deserialize(buf)?.0;
+
+        let len = VarU32::deserialize(buf)?.0 as usize;
+        if len > buf.remaining() {
+            let err = Error::OversizePrefix {
+                len,
+                max: buf.remaining(),
+            };
+            return Err(err.with_field("payload").into());
+        }
+
+        let mut payload = vec![0u8; len];
+        buf.read_exact(&mut payload)?;
+
+        Ok(Self { hash, payload })
+    }
+}
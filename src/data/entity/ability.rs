@@ -1,30 +1,40 @@
-use binary::datatypes::{F32, I64};
+use binary::datatypes::{F32, I64, U16, U32, U8};
 use binary_derive::Binary;
 use byteorder::LE;
 
+use crate::{binary_enum, data::SliceU8};
+
 pub const BASE_FLY_SPEED: f32 = 0.05;
 pub const BASE_WALK_SPEED: f32 = 0.1;
 
 #[derive(Debug, Default, Binary)]
 pub struct AbilityData {
     pub entity_unique_id: I64<LE>,
+    pub player_permissions: U8,
+    pub command_permissions: U8,
+    pub layers: SliceU8<AbilityLayer>,
 }
 
+/// AbilityLayer is a set of abilities of a player. The abilities and values are bitsets indexed
+/// by the abilities, where the abilities bitset marks which of the values are set by the layer.
 #[derive(Debug, Default, Binary)]
 pub struct AbilityLayer {
     pub layer_type: AbilityLayerType,
+    pub abilities: U32<LE>,
+    pub values: U32<LE>,
     pub fly_speed: F32<LE>,
     pub walk_speed: F32<LE>,
 }
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "U16")]
-pub enum AbilityLayerType {
-    Cache,
-    Base,
-    Spectator,
-    Commands,
-    Editor,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum AbilityLayerType: U16<LE> {
+        Cache,
+        Base,
+        Spectator,
+        Commands,
+        Editor,
+        #[default]
+        Invalid,
+    }
 }
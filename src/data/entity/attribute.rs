@@ -1,8 +1,11 @@
-use binary::datatypes::{Bool, F32};
+use binary::datatypes::{Bool, F32, I32};
 use binary_derive::Binary;
 use byteorder::LE;
 
-use crate::data::{Slice, VarString};
+use crate::{
+    binary_enum,
+    data::{Slice, VarString},
+};
 
 #[derive(Default, Debug, Binary)]
 pub struct Attribute {
@@ -24,23 +27,35 @@ pub struct AttributeModifier {
     pub serializable: Bool,
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "I32")]
-pub enum AttributeOperation {
-    Add,
-    MultiplyBase,
-    MultiplyTotal,
-    Cap,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum AttributeOperation: I32<LE> {
+        Add,
+        MultiplyBase,
+        MultiplyTotal,
+        Cap,
+        #[default]
+        Invalid,
+    }
+}
+
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum AttributeTargetOperand: I32<LE> {
+        Min,
+        Max,
+        Current,
+        #[default]
+        Invalid,
+    }
 }
 
+/// AttributeValue is an attribute of an entity as sent when the entity is spawned, which only
+/// carries its range and current value.
 #[derive(Default, Debug, Binary)]
-#[data(datatype = "I32")]
-pub enum AttributeTargetOperand {
-    Min,
-    Max,
-    Current,
-    #[default]
-    Invalid,
+pub struct AttributeValue {
+    pub name: VarString,
+    pub min: F32<LE>,
+    pub value: F32<LE>,
+    pub max: F32<LE>,
 }
@@ -0,0 +1,26 @@
+use binary::datatypes::{Bool, VarI64, F32, U8};
+use binary_derive::Binary;
+use byteorder::LE;
+
+use crate::binary_enum;
+
+/// EntityLink links a rider to the entity it is riding, such as a player riding a horse.
+#[derive(Debug, Default, Binary)]
+pub struct EntityLink {
+    pub ridden_entity_unique_id: VarI64,
+    pub rider_entity_unique_id: VarI64,
+    pub link_type: EntityLinkType,
+    pub immediate: Bool,
+    pub rider_initiated: Bool,
+    pub vehicle_angular_velocity: F32<LE>,
+}
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum EntityLinkType: U8 {
+        #[default]
+        Remove,
+        Rider,
+        Passenger,
+    }
+}
@@ -0,0 +1,499 @@
+use std::{
+    collections::BTreeMap,
+    io::{Read, Result, Write},
+};
+
+use binary::{
+    binary::Binary,
+    datatypes::{VarI32, VarI64, VarU32, F32, I16, U8},
+};
+use byteorder::LE;
+use bytes::Buf;
+use nbt::{encoding::NetworkLittleEndian, NBTCompound};
+
+use crate::{
+    data::{BlockPos, Position, VarString},
+    error::Error,
+};
+
+/// MetadataKey is the key of an entry in the metadata of an entity. It is not an enum as new keys
+/// are added by almost every version of the game, and the entries with keys unknown to this
+/// library still have to be kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MetadataKey(pub u32);
+
+impl MetadataKey {
+    pub const FLAGS: Self = Self(0);
+    pub const STRUCTURAL_INTEGRITY: Self = Self(1);
+    pub const VARIANT: Self = Self(2);
+    pub const COLOR_INDEX: Self = Self(3);
+    pub const NAME: Self = Self(4);
+    pub const OWNER: Self = Self(5);
+    pub const TARGET: Self = Self(6);
+    pub const AIR_SUPPLY: Self = Self(7);
+    pub const EFFECT_COLOR: Self = Self(8);
+    pub const EFFECT_AMBIENCE: Self = Self(9);
+    pub const JUMP_DURATION: Self = Self(10);
+    pub const HURT: Self = Self(11);
+    pub const HURT_DIRECTION: Self = Self(12);
+    pub const ROW_TIME_LEFT: Self = Self(13);
+    pub const ROW_TIME_RIGHT: Self = Self(14);
+    pub const VALUE: Self = Self(15);
+    pub const DISPLAY_TILE_RUNTIME_ID: Self = Self(16);
+    pub const DISPLAY_OFFSET: Self = Self(17);
+    pub const CUSTOM_DISPLAY: Self = Self(18);
+    pub const SWELL: Self = Self(19);
+    pub const OLD_SWELL: Self = Self(20);
+    pub const SWELL_DIRECTION: Self = Self(21);
+    pub const CHARGE_AMOUNT: Self = Self(22);
+    pub const CARRY_BLOCK_RUNTIME_ID: Self = Self(23);
+    pub const CLIENT_EVENT: Self = Self(24);
+    pub const USING_ITEM: Self = Self(25);
+    pub const PLAYER_FLAGS: Self = Self(26);
+    pub const PLAYER_INDEX: Self = Self(27);
+    pub const BED_POSITION: Self = Self(28);
+    pub const POWER_X: Self = Self(29);
+    pub const POWER_Y: Self = Self(30);
+    pub const POWER_Z: Self = Self(31);
+    pub const AUX_POWER: Self = Self(32);
+    pub const FISH_X: Self = Self(33);
+    pub const FISH_Z: Self = Self(34);
+    pub const FISH_ANGLE: Self = Self(35);
+    pub const AUX_VALUE_DATA: Self = Self(36);
+    pub const LEASH_HOLDER: Self = Self(37);
+    pub const SCALE: Self = Self(38);
+    pub const HAS_NPC: Self = Self(39);
+    pub const NPC_DATA: Self = Self(40);
+    pub const ACTIONS: Self = Self(41);
+    pub const AIR_SUPPLY_MAX: Self = Self(42);
+    pub const MARK_VARIANT: Self = Self(43);
+    pub const CONTAINER_TYPE: Self = Self(44);
+    pub const CONTAINER_SIZE: Self = Self(45);
+    pub const CONTAINER_STRENGTH_MODIFIER: Self = Self(46);
+    pub const BLOCK_TARGET: Self = Self(47);
+    pub const INVENTORY: Self = Self(48);
+    pub const TARGET_A: Self = Self(49);
+    pub const TARGET_B: Self = Self(50);
+    pub const TARGET_C: Self = Self(51);
+    pub const AERIAL_ATTACK: Self = Self(52);
+    pub const WIDTH: Self = Self(53);
+    pub const HEIGHT: Self = Self(54);
+    pub const FUSE_TIME: Self = Self(55);
+    pub const SEAT_OFFSET: Self = Self(56);
+    pub const SEAT_LOCK_PASSENGER_ROTATION: Self = Self(57);
+    pub const SEAT_LOCK_PASSENGER_ROTATION_DEGREES: Self = Self(58);
+    pub const SEAT_ROTATION_OFFSET: Self = Self(59);
+    pub const SEAT_ROTATION_OFFSET_DEGREES: Self = Self(60);
+    pub const DATA_RADIUS: Self = Self(61);
+    pub const DATA_WAITING: Self = Self(62);
+    pub const DATA_PARTICLE: Self = Self(63);
+    pub const PEAK_TIME: Self = Self(64);
+    pub const ATTACH_FACE: Self = Self(65);
+    pub const ATTACHED: Self = Self(66);
+    pub const ATTACHED_POSITION: Self = Self(67);
+    pub const TRADE_TARGET: Self = Self(68);
+    pub const CAREER: Self = Self(69);
+    pub const HAS_COMMAND_BLOCK: Self = Self(70);
+    pub const COMMAND_NAME: Self = Self(71);
+    pub const LAST_COMMAND_OUTPUT: Self = Self(72);
+    pub const TRACK_COMMAND_OUTPUT: Self = Self(73);
+    pub const CONTROLLING_SEAT_INDEX: Self = Self(74);
+    pub const STRENGTH: Self = Self(75);
+    pub const STRENGTH_MAX: Self = Self(76);
+    pub const DATA_SPELL_CASTING_COLOR: Self = Self(77);
+    pub const DATA_LIFETIME_TICKS: Self = Self(78);
+    pub const POSE_INDEX: Self = Self(79);
+    pub const DATA_TICK_OFFSET: Self = Self(80);
+    pub const ALWAYS_SHOW_NAME_TAG: Self = Self(81);
+    pub const COLOR_TWO_INDEX: Self = Self(82);
+    pub const NAME_AUTHOR: Self = Self(83);
+    pub const SCORE: Self = Self(84);
+    pub const BALLOON_ANCHOR: Self = Self(85);
+    pub const PUFFED_STATE: Self = Self(86);
+    pub const BUBBLE_TIME: Self = Self(87);
+    pub const AGENT: Self = Self(88);
+    pub const SITTING_AMOUNT: Self = Self(89);
+    pub const SITTING_AMOUNT_PREVIOUS: Self = Self(90);
+    pub const EATING_COUNTER: Self = Self(91);
+    pub const FLAGS_2: Self = Self(92);
+    pub const LAYING_AMOUNT: Self = Self(93);
+    pub const LAYING_AMOUNT_PREVIOUS: Self = Self(94);
+    pub const DATA_DURATION: Self = Self(95);
+    pub const DATA_SPAWN_TIME: Self = Self(96);
+    pub const DATA_CHANGE_RATE: Self = Self(97);
+    pub const DATA_CHANGE_ON_PICKUP: Self = Self(98);
+    pub const DATA_PICKUP_COUNT: Self = Self(99);
+    pub const INTERACT_TEXT: Self = Self(100);
+    pub const TRADE_TIER: Self = Self(101);
+    pub const MAX_TRADE_TIER: Self = Self(102);
+    pub const TRADE_EXPERIENCE: Self = Self(103);
+    pub const SKIN_ID: Self = Self(104);
+    pub const SPAWNING_FRAMES: Self = Self(105);
+    pub const COMMAND_BLOCK_TICK_DELAY: Self = Self(106);
+    pub const COMMAND_BLOCK_EXECUTE_ON_FIRST_TICK: Self = Self(107);
+    pub const AMBIENT_SOUND_INTERVAL: Self = Self(108);
+    pub const AMBIENT_SOUND_INTERVAL_RANGE: Self = Self(109);
+    pub const AMBIENT_SOUND_EVENT_NAME: Self = Self(110);
+    pub const FALL_DAMAGE_MULTIPLIER: Self = Self(111);
+    pub const NAME_RAW_TEXT: Self = Self(112);
+    pub const CAN_RIDE_TARGET: Self = Self(113);
+    pub const LOW_TIER_CURED_TRADE_DISCOUNT: Self = Self(114);
+    pub const HIGH_TIER_CURED_TRADE_DISCOUNT: Self = Self(115);
+    pub const NEARBY_CURED_TRADE_DISCOUNT: Self = Self(116);
+    pub const NEARBY_CURED_DISCOUNT_TIME_STAMP: Self = Self(117);
+    pub const HIT_BOX: Self = Self(118);
+    pub const IS_BUOYANT: Self = Self(119);
+    pub const FREEZING_EFFECT_STRENGTH: Self = Self(120);
+    pub const BUOYANCY_DATA: Self = Self(121);
+    pub const GOAT_HORN_COUNT: Self = Self(122);
+    pub const BASE_RUNTIME_ID: Self = Self(123);
+    pub const MOVEMENT_SOUND_DISTANCE_OFFSET: Self = Self(124);
+    pub const HEARTBEAT_INTERVAL_TICKS: Self = Self(125);
+    pub const HEARTBEAT_SOUND_EVENT: Self = Self(126);
+    pub const PLAYER_LAST_DEATH_POSITION: Self = Self(127);
+    pub const PLAYER_LAST_DEATH_DIMENSION: Self = Self(128);
+    pub const PLAYER_HAS_DIED: Self = Self(129);
+    pub const COLLISION_BOX: Self = Self(130);
+}
+
+/// MetadataValue is the value of an entry in the metadata of an entity. The variants are encoded
+/// with the ID of their type before the value.
+#[derive(Debug)]
+pub enum MetadataValue {
+    Byte(U8),
+    Short(I16<LE>),
+    Int(VarI32),
+    Float(F32<LE>),
+    String(VarString),
+    Compound(NBTCompound<NetworkLittleEndian>),
+    BlockPos(BlockPos),
+    Long(VarI64),
+    Position(Position),
+}
+
+impl MetadataValue {
+    /// Returns the ID of the type of the value.
+    pub fn type_id(&self) -> u32 {
+        match self {
+            Self::Byte(_) => 0,
+            Self::Short(_) => 1,
+            Self::Int(_) => 2,
+            Self::Float(_) => 3,
+            Self::String(_) => 4,
+            Self::Compound(_) => 5,
+            Self::BlockPos(_) => 6,
+            Self::Long(_) => 7,
+            Self::Position(_) => 8,
+        }
+    }
+}
+
+/// The type ID of the value is not known until the value is read, so the enum is encoded by hand
+/// in order to report unknown type IDs instead of falling back to a default variant.
+impl Binary for MetadataValue {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        VarU32::new(self.type_id()).serialize(buf);
+
+        match self {
+            Self::Byte(value) => value.serialize(buf),
+            Self::Short(value) => value.serialize(buf),
+            Self::Int(value) => value.serialize(buf),
+            Self::Float(value) => value.serialize(buf),
+            Self::String(value) => value.serialize(buf),
+            Self::Compound(value) => value.serialize(buf),
+            Self::BlockPos(value) => value.serialize(buf),
+            Self::Long(value) => value.serialize(buf),
+            Self::Position(value) => value.serialize(buf),
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let value = match VarU32::deserialize(buf)?.0 {
+            0 => Self::Byte(U8::deserialize(buf)?),
+            1 => Self::Short(I16::deserialize(buf)?),
+            2 => Self::Int(VarI32::deserialize(buf)?),
+            3 => Self::Float(F32::deserialize(buf)?),
+            4 => Self::String(VarString::deserialize(buf)?),
+            5 => Self::Compound(NBTCompound::deserialize(buf)?),
+            6 => Self::BlockPos(BlockPos::deserialize(buf)?),
+            7 => Self::Long(VarI64::deserialize(buf)?),
+            8 => Self::Position(Position::deserialize(buf)?),
+            value => {
+                let err = Error::InvalidDiscriminant {
+                    ty: "MetadataValue",
+                    value: value as i64,
+                };
+                return Err(err.into());
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+/// EntityFlag is a bit of the 128-bit flags of an entity. The first 64 flags are stored in the
+/// [`MetadataKey::FLAGS`] entry and the rest of them in the [`MetadataKey::FLAGS_2`] entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EntityFlag {
+    OnFire,
+    Sneaking,
+    Riding,
+    Sprinting,
+    UsingItem,
+    Invisible,
+    Tempted,
+    InLove,
+    Saddled,
+    Powered,
+    Ignited,
+    Baby,
+    Converting,
+    Critical,
+    ShowName,
+    AlwaysShowName,
+    NoAI,
+    Silent,
+    WallClimbing,
+    Climb,
+    Swim,
+    Fly,
+    Walk,
+    Resting,
+    Sitting,
+    Angry,
+    Interested,
+    Charged,
+    Tamed,
+    Orphaned,
+    Leashed,
+    Sheared,
+    Gliding,
+    Elder,
+    Moving,
+    Breathing,
+    Chested,
+    Stackable,
+    ShowBottom,
+    Standing,
+    Shaking,
+    Idling,
+    Casting,
+    Charging,
+    KeyboardControlled,
+    PowerJump,
+    Dash,
+    Lingering,
+    HasCollision,
+    HasGravity,
+    FireImmune,
+    Dancing,
+    Enchanted,
+    ReturnTrident,
+    ContainerPrivate,
+    Transforming,
+    DamageNearbyMobs,
+    Swimming,
+    Bribed,
+    Pregnant,
+    LayingEgg,
+    PassengerCanPick,
+    TransitionSitting,
+    Eating,
+    LayingDown,
+    Sneezing,
+    Trusting,
+    Rolling,
+    Scared,
+    InScaffolding,
+    OverScaffolding,
+    DescendThroughBlock,
+    Blocking,
+    TransitionBlocking,
+    BlockedUsingShield,
+    BlockedUsingDamagedShield,
+    Sleeping,
+    WantsToWake,
+    TradeInterest,
+    DoorBreaker,
+    BreakingObstruction,
+    DoorOpener,
+    Captain,
+    Stunned,
+    Roaring,
+    DelayedAttack,
+    AvoidingMobs,
+    AvoidingBlock,
+    FacingTargetToRangeAttack,
+    HiddenWhenInvisible,
+    InUI,
+    Stalking,
+    Emoting,
+    Celebrating,
+    Admiring,
+    CelebratingSpecial,
+    OutOfControl,
+    RamAttack,
+    PlayingDead,
+    InAscendingBlock,
+    OverDescendingBlock,
+    Croaking,
+    DigestMob,
+    JumpGoal,
+    Emerging,
+    Sniffing,
+    Digging,
+    SonicBoom,
+    HasDashTimeout,
+    PushTowardsClosestSpace,
+    Scenting,
+    Rising,
+    FeelingHappy,
+    Searching,
+    Crawling,
+}
+
+/// EntityFlags is the 128-bit set of the flags of an entity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntityFlags(pub u128);
+
+impl EntityFlags {
+    /// Returns true if the flag is set.
+    pub fn get(&self, flag: EntityFlag) -> bool {
+        self.0 & (1u128 << flag as u8) != 0
+    }
+
+    /// Sets or clears the flag.
+    pub fn set(&mut self, flag: EntityFlag, value: bool) {
+        if value {
+            self.0 |= 1u128 << flag as u8;
+        } else {
+            self.0 &= !(1u128 << flag as u8);
+        }
+    }
+
+    /// Returns the flags with the flag set.
+    pub fn with(mut self, flag: EntityFlag) -> Self {
+        self.set(flag, true);
+        self
+    }
+
+    /// Returns the first 64 flags, as stored in the [`MetadataKey::FLAGS`] entry.
+    pub fn low(&self) -> i64 {
+        self.0 as u64 as i64
+    }
+
+    /// Returns the last 64 flags, as stored in the [`MetadataKey::FLAGS_2`] entry.
+    pub fn high(&self) -> i64 {
+        (self.0 >> 64) as u64 as i64
+    }
+
+    /// Creates and returns the flags from the values of the two flag entries.
+    pub fn from_parts(low: i64, high: i64) -> Self {
+        Self((low as u64 as u128) | ((high as u64 as u128) << 64))
+    }
+}
+
+/// EntityMetadata holds the properties of an entity that decide how it is rendered by the client,
+/// such as its flags, name tag, scale and bounding box. The entries are kept sorted by their key.
+#[derive(Debug, Default)]
+pub struct EntityMetadata {
+    entries: BTreeMap<MetadataKey, MetadataValue>,
+}
+
+impl EntityMetadata {
+    /// Creates and returns a new empty EntityMetadata.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of the entry with the key, if any.
+    pub fn get(&self, key: MetadataKey) -> Option<&MetadataValue> {
+        self.entries.get(&key)
+    }
+
+    /// Sets the value of the entry with the key, returning the previous value if any.
+    pub fn insert(&mut self, key: MetadataKey, value: MetadataValue) -> Option<MetadataValue> {
+        self.entries.insert(key, value)
+    }
+
+    /// Removes the entry with the key, returning its value if any.
+    pub fn remove(&mut self, key: MetadataKey) -> Option<MetadataValue> {
+        self.entries.remove(&key)
+    }
+
+    /// Returns an iterator over the entries, ordered by their key.
+    pub fn iter(&self) -> impl Iterator<Item = (&MetadataKey, &MetadataValue)> {
+        self.entries.iter()
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the flags of the entity. Missing flag entries are treated as if no flag is set.
+    pub fn flags(&self) -> EntityFlags {
+        let long = |key| match self.entries.get(&key) {
+            Some(MetadataValue::Long(value)) => value.0,
+            _ => 0,
+        };
+
+        EntityFlags::from_parts(long(MetadataKey::FLAGS), long(MetadataKey::FLAGS_2))
+    }
+
+    /// Sets the flags of the entity, writing both flag entries.
+    pub fn set_flags(&mut self, flags: EntityFlags) {
+        self.entries.insert(
+            MetadataKey::FLAGS,
+            MetadataValue::Long(VarI64::new(flags.low())),
+        );
+        self.entries.insert(
+            MetadataKey::FLAGS_2,
+            MetadataValue::Long(VarI64::new(flags.high())),
+        );
+    }
+
+    /// Returns true if the flag of the entity is set.
+    pub fn flag(&self, flag: EntityFlag) -> bool {
+        self.flags().get(flag)
+    }
+
+    /// Sets or clears the flag of the entity.
+    pub fn set_flag(&mut self, flag: EntityFlag, value: bool) {
+        let mut flags = self.flags();
+        flags.set(flag, value);
+        self.set_flags(flags);
+    }
+}
+
+impl Binary for EntityMetadata {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        VarU32::new(self.entries.len() as u32).serialize(buf);
+
+        for (key, value) in &self.entries {
+            VarU32::new(key.0).serialize(buf);
+            value.serialize(buf);
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let len = VarU32::deserialize(buf)?.0;
+        let mut entries = BTreeMap::new();
+
+        for _ in 0..len {
+            let key = MetadataKey(VarU32::deserialize(buf)?.0);
+            let value = MetadataValue::deserialize(buf)
+                .map_err(|err| Error::from_io(err).with_field("value"))?;
+
+            entries.insert(key, value);
+        }
+
+        Ok(Self { entries })
+    }
+}
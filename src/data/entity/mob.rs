@@ -1,46 +1,49 @@
-use binary_derive::Binary;
+use binary::datatypes::{VarI32, U8};
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "U8")]
-pub enum MobEffectOperation {
-    #[variant(tag = 1)]
-    Add,
-    Modify,
-    Remove,
-    #[default]
-    Invalid,
+use crate::binary_enum;
+
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum MobEffectOperation: U8 {
+        Add = 1,
+        Modify,
+        Remove,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-pub enum MobEffectType {
-    #[variant(tag = 1)]
-    Speed,
-    Slowness,
-    Haste,
-    MiningFatigue,
-    Strength,
-    InstantHealth,
-    InstantDamage,
-    JumpBoost,
-    Nausea,
-    Regeneration,
-    Resistance,
-    FireResistance,
-    WaterBreathing,
-    Invisibility,
-    Blindness,
-    NightVision,
-    Hunger,
-    Weakness,
-    Poison,
-    Wither,
-    HealthBoost,
-    Absorption,
-    Saturation,
-    Levitation,
-    FatalPoison,
-    ConduitPower,
-    SlowFalling,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum MobEffectType: VarI32 {
+        Speed = 1,
+        Slowness,
+        Haste,
+        MiningFatigue,
+        Strength,
+        InstantHealth,
+        InstantDamage,
+        JumpBoost,
+        Nausea,
+        Regeneration,
+        Resistance,
+        FireResistance,
+        WaterBreathing,
+        Invisibility,
+        Blindness,
+        NightVision,
+        Hunger,
+        Weakness,
+        Poison,
+        Wither,
+        HealthBoost,
+        Absorption,
+        Saturation,
+        Levitation,
+        FatalPoison,
+        ConduitPower,
+        SlowFalling,
+        #[default]
+        Invalid,
+    }
 }
@@ -1,5 +1,8 @@
 pub mod ability;
 pub mod attribute;
+pub mod link;
+pub mod metadata;
 pub mod mob;
 pub mod npc;
+pub mod property;
 pub mod teleport;
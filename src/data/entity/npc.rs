@@ -1,15 +1,18 @@
-use binary_derive::Binary;
+use binary::datatypes::U8;
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "U8")]
-pub enum NPCRequestType {
-    SetAction,
-    ExecuteCommandAction,
-    ExecuteClosingCommands,
-    SetName,
-    SetSkin,
-    SetInteractionText,
-    ExecuteOpeningCommands,
-    #[default]
-    Invalid,
+use crate::binary_enum;
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum NPCRequestType: U8 {
+        SetAction,
+        ExecuteCommandAction,
+        ExecuteClosingCommands,
+        SetName,
+        SetSkin,
+        SetInteractionText,
+        ExecuteOpeningCommands,
+        #[default]
+        Invalid,
+    }
 }
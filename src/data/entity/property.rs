@@ -0,0 +1,26 @@
+use binary::datatypes::{VarI32, VarU32, F32};
+use binary_derive::Binary;
+use byteorder::LE;
+
+use crate::data::Slice;
+
+/// EntityProperties holds the values of the custom properties of an entity, as defined by the
+/// property data of the behaviour pack of the entity.
+#[derive(Debug, Default, Binary)]
+pub struct EntityProperties {
+    pub integer_properties: Slice<IntegerEntityProperty>,
+    pub float_properties: Slice<FloatEntityProperty>,
+}
+
+/// IntegerEntityProperty is an integer, boolean or enum property of an entity.
+#[derive(Debug, Default, Binary)]
+pub struct IntegerEntityProperty {
+    pub index: VarU32,
+    pub value: VarI32,
+}
+
+#[derive(Debug, Default, Binary)]
+pub struct FloatEntityProperty {
+    pub index: VarU32,
+    pub value: F32<LE>,
+}
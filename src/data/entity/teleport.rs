@@ -1,23 +1,28 @@
-use binary_derive::Binary;
+use binary::datatypes::{I32, U8};
+use byteorder::LE;
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "I32")]
-pub enum MovementMode {
-    Normal,
-    Reset,
-    Teleportation,
-    Rotation,
-    #[default]
-    Invalid,
+use crate::binary_enum;
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum MovementMode: I32<LE> {
+        Normal,
+        Reset,
+        Teleportation,
+        Rotation,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "U8")]
-pub enum TeleportCause {
-    #[default]
-    Unknown,
-    Projectile,
-    ChorusFruit,
-    Command,
-    Behaviour,
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum TeleportCause: U8 {
+        #[default]
+        Unknown,
+        Projectile,
+        ChorusFruit,
+        Command,
+        Behaviour,
+    }
 }
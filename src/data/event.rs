@@ -1,209 +1,193 @@
-use binary_derive::Binary;
+use binary::datatypes::{VarI32, U8};
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "VarI32")]
-pub enum LevelEventType {
-    #[variant(tag = 1000)]
-    SoundClick,
-    SoundClickFail,
-    SoundLaunch,
-    SoundOpenDoor,
-    SoundFizz,
-    SoundFuse,
-    SoundPlayRecording,
-    SoundGhastWarning,
-    SoundGhastFireball,
-    SoundBlazeFireball,
-    SoundZombieWoodenDoor,
-    #[variant(tag = 1012)]
-    SoundZombieDoorCrash,
-    #[variant(tag = 1016)]
-    SoundZombieInfected,
-    SoundZombieConverted,
-    SoundEndermanTeleport,
-    #[variant(tag = 1020)]
-    SoundAnvilBroken,
-    SoundAnvilUsed,
-    SoundAnvilLand,
-    #[variant(tag = 1030)]
-    SoundInfinityArrowPickup,
-    #[variant(tag = 1032)]
-    SoundTeleportEnderPearl,
-    #[variant(tag = 1040)]
-    SoundAddItem,
-    SoundItemFrameBreak,
-    SoundItemFramePlace,
-    SoundItemFrameRemoveItem,
-    SoundItemFrameRotateItem,
-    #[variant(tag = 1051)]
-    SoundExperienceOrbPickup,
-    SoundTotemUsed,
-    #[variant(tag = 1060)]
-    SoundArmorStandBreak,
-    SoundArmorStandHit,
-    SoundArmorStandLand,
-    SoundArmorStandPlace,
-    SoundPointedDripstoneLand,
-    SoundDyeUsed,
-    SoundInkSacUsed,
-    SoundAmethystResonate,
-    #[variant(tag = 1900)]
-    QueueCustomMusic,
-    PlayCustomMusic,
-    StopCustomMusic,
-    SetMusicVolume,
-    #[variant(tag = 2000)]
-    ParticlesShoot,
-    ParticlesDestroyBlock,
-    ParticlesPotionSplash,
-    ParticlesEyeOfEnderDeath,
-    ParticlesMobBlockSpawn,
-    ParticleCropGrowth,
-    ParticleSoundGuardianGhost,
-    ParticleDeathSmoke,
-    ParticleDenyBlock,
-    ParticleGenericSpawn,
-    ParticlesDragonEgg,
-    ParticlesCropEaten,
-    ParticlesCritical,
-    ParticlesTeleport,
-    ParticlesCrackBlock,
-    ParticlesBubble,
-    ParticlesEvaporate,
-    ParticlesDestroyArmorStand,
-    ParticlesBreakingEgg,
-    ParticleDestroyEgg,
-    ParticlesEvaporateWater,
-    ParticlesDestroyBlockNoSound,
-    ParticlesKnockbackRoar,
-    ParticlesTeleportTrail,
-    ParticlesPointCloud,
-    ParticlesExplosion,
-    ParticlesBlockExplosion,
-    ParticlesVibrationSignal,
-    ParticlesDripstoneDrip,
-    ParticlesFizzEffect,
-    WaxOn,
-    WaxOff,
-    Scrape,
-    ParticlesElectricSpark,
-    ParticleTurtleEgg,
-    ParticleSculkShriek,
-    SculkCatalystBloom,
-    SculkCharge,
-    SculkChargePop,
-    SonicExplosion,
-    #[variant(tag = 3001)]
-    StartRaining,
-    StartThunderstorm,
-    StopRaining,
-    StopThunderstorm,
-    GlobalPause,
-    SimTimeStep,
-    SimTimeScale,
-    #[variant(tag = 3500)]
-    ActivateBlock,
-    CauldronExplode,
-    CauldronDyeArmor,
-    CauldronCleanArmor,
-    CauldronFillPotion,
-    CauldronTakePotion,
-    CauldronFillWater,
-    CauldronTakeWater,
-    CauldronAddDye,
-    CauldronCleanBanner,
-    CauldronFlush,
-    AgentSpawnEffect,
-    CauldronFillLava,
-    CauldronTakeLava,
-    CauldronFillPowderSnow,
-    CauldronTakePowderSnow,
-    #[variant(tag = 3600)]
-    StartBlockCracking,
-    StopBlockCracking,
-    UpdateBlockCracking,
-    ParticlesCrackBlockDown,
-    ParticlesCrackBlockUp,
-    ParticlesCrackBlockNorth,
-    ParticlesCrackBlockSouth,
-    ParticlesCrackBlockWest,
-    ParticlesCrackBlockEast,
-    #[variant(tag = 9800)]
-    AllPlayersSleeping,
-    SleepingPlayers,
-    #[variant(tag = 9810)]
-    JumpPrevented,
-    #[variant(tag = 16384)]
-    ParticleLegacyEvent,
-    #[default]
-    Invalid,
+use crate::binary_enum;
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum LevelEventType: VarI32 {
+        SoundClick = 1000,
+        SoundClickFail,
+        SoundLaunch,
+        SoundOpenDoor,
+        SoundFizz,
+        SoundFuse,
+        SoundPlayRecording,
+        SoundGhastWarning,
+        SoundGhastFireball,
+        SoundBlazeFireball,
+        SoundZombieWoodenDoor,
+        SoundZombieDoorCrash = 1012,
+        SoundZombieInfected = 1016,
+        SoundZombieConverted,
+        SoundEndermanTeleport,
+        SoundAnvilBroken = 1020,
+        SoundAnvilUsed,
+        SoundAnvilLand,
+        SoundInfinityArrowPickup = 1030,
+        SoundTeleportEnderPearl = 1032,
+        SoundAddItem = 1040,
+        SoundItemFrameBreak,
+        SoundItemFramePlace,
+        SoundItemFrameRemoveItem,
+        SoundItemFrameRotateItem,
+        SoundExperienceOrbPickup = 1051,
+        SoundTotemUsed,
+        SoundArmorStandBreak = 1060,
+        SoundArmorStandHit,
+        SoundArmorStandLand,
+        SoundArmorStandPlace,
+        SoundPointedDripstoneLand,
+        SoundDyeUsed,
+        SoundInkSacUsed,
+        SoundAmethystResonate,
+        QueueCustomMusic = 1900,
+        PlayCustomMusic,
+        StopCustomMusic,
+        SetMusicVolume,
+        ParticlesShoot = 2000,
+        ParticlesDestroyBlock,
+        ParticlesPotionSplash,
+        ParticlesEyeOfEnderDeath,
+        ParticlesMobBlockSpawn,
+        ParticleCropGrowth,
+        ParticleSoundGuardianGhost,
+        ParticleDeathSmoke,
+        ParticleDenyBlock,
+        ParticleGenericSpawn,
+        ParticlesDragonEgg,
+        ParticlesCropEaten,
+        ParticlesCritical,
+        ParticlesTeleport,
+        ParticlesCrackBlock,
+        ParticlesBubble,
+        ParticlesEvaporate,
+        ParticlesDestroyArmorStand,
+        ParticlesBreakingEgg,
+        ParticleDestroyEgg,
+        ParticlesEvaporateWater,
+        ParticlesDestroyBlockNoSound,
+        ParticlesKnockbackRoar,
+        ParticlesTeleportTrail,
+        ParticlesPointCloud,
+        ParticlesExplosion,
+        ParticlesBlockExplosion,
+        ParticlesVibrationSignal,
+        ParticlesDripstoneDrip,
+        ParticlesFizzEffect,
+        WaxOn,
+        WaxOff,
+        Scrape,
+        ParticlesElectricSpark,
+        ParticleTurtleEgg,
+        ParticleSculkShriek,
+        SculkCatalystBloom,
+        SculkCharge,
+        SculkChargePop,
+        SonicExplosion,
+        StartRaining = 3001,
+        StartThunderstorm,
+        StopRaining,
+        StopThunderstorm,
+        GlobalPause,
+        SimTimeStep,
+        SimTimeScale,
+        ActivateBlock = 3500,
+        CauldronExplode,
+        CauldronDyeArmor,
+        CauldronCleanArmor,
+        CauldronFillPotion,
+        CauldronTakePotion,
+        CauldronFillWater,
+        CauldronTakeWater,
+        CauldronAddDye,
+        CauldronCleanBanner,
+        CauldronFlush,
+        AgentSpawnEffect,
+        CauldronFillLava,
+        CauldronTakeLava,
+        CauldronFillPowderSnow,
+        CauldronTakePowderSnow,
+        StartBlockCracking = 3600,
+        StopBlockCracking,
+        UpdateBlockCracking,
+        ParticlesCrackBlockDown,
+        ParticlesCrackBlockUp,
+        ParticlesCrackBlockNorth,
+        ParticlesCrackBlockSouth,
+        ParticlesCrackBlockWest,
+        ParticlesCrackBlockEast,
+        AllPlayersSleeping = 9800,
+        SleepingPlayers,
+        JumpPrevented = 9810,
+        ParticleLegacyEvent = 16384,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "U8")]
-pub enum ActorEventType {
-    #[variant(tag = 1)]
-    Jump,
-    Hurt,
-    Death,
-    StartAttacking,
-    StopAttacking,
-    TamingFailed,
-    TamingSucceeded,
-    ShakeWetness,
-    UseItem,
-    EatGrass,
-    FishhookBubble,
-    FishhookFishPosition,
-    FishhookHookTime,
-    FishhookTease,
-    SquidFleeing,
-    ZombieConverting,
-    PlayAmbient,
-    SpawnAlive,
-    StartOfferFlower,
-    StopOfferFlower,
-    LoveHearts,
-    VillagerAngry,
-    VillagerHappy,
-    WitchHatMagic,
-    FireworksExplode,
-    InLoveHearts,
-    SilverfishMergeAnimation,
-    GuardianAttackSound,
-    DrinkPotion,
-    ThrowPotion,
-    CartWithPrimeTNT,
-    PrimeCreeper,
-    AirSupply,
-    AddPlayerLevels,
-    GuardianMiningFatigue,
-    AgentSwingArm,
-    DragonStartDeathAnim,
-    GroundDust,
-    Shake,
-    #[variant(tag = 57)]
-    Feed,
-    #[variant(tag = 60)]
-    BabyEat,
-    InstantDeath,
-    NotifyTrade,
-    LeashDestroyed,
-    CaravanUpdated,
-    TalismanActivate,
-    UpdateStructureFeature,
-    PlayerSpawnedMob,
-    Puke,
-    UpdateStackSize,
-    StartSwimming,
-    BalloonPop,
-    TreasureHunt,
-    SummonAgent,
-    FinishedChargingItem,
-    LandedOnGround,
-    ActorGrowUp,
-    VibrationDetected,
-    DrinkMilk,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum ActorEventType: U8 {
+        Jump = 1,
+        Hurt,
+        Death,
+        StartAttacking,
+        StopAttacking,
+        TamingFailed,
+        TamingSucceeded,
+        ShakeWetness,
+        UseItem,
+        EatGrass,
+        FishhookBubble,
+        FishhookFishPosition,
+        FishhookHookTime,
+        FishhookTease,
+        SquidFleeing,
+        ZombieConverting,
+        PlayAmbient,
+        SpawnAlive,
+        StartOfferFlower,
+        StopOfferFlower,
+        LoveHearts,
+        VillagerAngry,
+        VillagerHappy,
+        WitchHatMagic,
+        FireworksExplode,
+        InLoveHearts,
+        SilverfishMergeAnimation,
+        GuardianAttackSound,
+        DrinkPotion,
+        ThrowPotion,
+        CartWithPrimeTNT,
+        PrimeCreeper,
+        AirSupply,
+        AddPlayerLevels,
+        GuardianMiningFatigue,
+        AgentSwingArm,
+        DragonStartDeathAnim,
+        GroundDust,
+        Shake,
+        Feed = 57,
+        BabyEat = 60,
+        InstantDeath,
+        NotifyTrade,
+        LeashDestroyed,
+        CaravanUpdated,
+        TalismanActivate,
+        UpdateStructureFeature,
+        PlayerSpawnedMob,
+        Puke,
+        UpdateStackSize,
+        StartSwimming,
+        BalloonPop,
+        TreasureHunt,
+        SummonAgent,
+        FinishedChargingItem,
+        LandedOnGround,
+        ActorGrowUp,
+        VibrationDetected,
+        DrinkMilk,
+        #[default]
+        Invalid,
+    }
 }
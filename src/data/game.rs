@@ -1,7 +1,9 @@
-use binary::datatypes::{Bool, F32, U32};
+use binary::datatypes::{Bool, VarI32, F32, I16, U32, U8};
 use binary_derive::Binary;
 use byteorder::LE;
 
+use crate::binary_enum;
+
 use super::VarString;
 
 #[derive(Default, Debug, Binary)]
@@ -27,65 +29,71 @@ pub enum GameRuleData {
     Invalid,
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "VarI32")]
-pub enum GameType {
-    Survival,
-    Creative,
-    Adventure,
-    SurvivalSpectator,
-    CreativeSpectator,
-    #[default]
-    Fallback,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum GameType: VarI32 {
+        Survival,
+        Creative,
+        Adventure,
+        SurvivalSpectator,
+        CreativeSpectator,
+        #[default]
+        Fallback,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "I16")]
-pub enum SpawnBiomeType {
-    #[default]
-    Default,
-    UserDefined,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum SpawnBiomeType: I16<LE> {
+        #[default]
+        Default,
+        UserDefined,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "VarI32")]
-pub enum EditorWorldType {
-    NotEditor,
-    Project,
-    TestLevel,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum EditorWorldType: VarI32 {
+        NotEditor,
+        Project,
+        TestLevel,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "VarI32")]
-pub enum GamePublishSetting {
-    NoMultiplayer,
-    InviteOnly,
-    FriendsOnly,
-    FriendsOfFriends,
-    Public,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum GamePublishSetting: VarI32 {
+        NoMultiplayer,
+        InviteOnly,
+        FriendsOnly,
+        FriendsOfFriends,
+        Public,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "VarI32")]
-pub enum PlayerPermission {
-    Visitor,
-    Member,
-    Operator,
-    Custom,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum PlayerPermission: VarI32 {
+        Visitor,
+        Member,
+        Operator,
+        Custom,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "U8")]
-pub enum ChatRestriction {
-    None,
-    Dropped,
-    Disabled,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum ChatRestriction: U8 {
+        None,
+        Dropped,
+        Disabled,
+        #[default]
+        Invalid,
+    }
 }
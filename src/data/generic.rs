@@ -1,49 +1,54 @@
-use binary_derive::Binary;
+use binary::datatypes::{I32, U8};
+use byteorder::BE;
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "I32BE")]
-pub enum PlayStatusType {
-    Success,
-    OutdatedClient,
-    OutdatedServer,
-    PlayerSpawn,
-    InvalidTenant,
-    EduToVanillaMismatch,
-    VanillaToEduMismatch,
-    ServerIsFull,
-    EditorToVanillaMismatch,
-    VanillaToEditorMismatch,
-    #[default]
-    Invalid,
+use crate::binary_enum;
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum PlayStatusType: I32<BE> {
+        Success,
+        OutdatedClient,
+        OutdatedServer,
+        PlayerSpawn,
+        InvalidTenant,
+        EduToVanillaMismatch,
+        VanillaToEduMismatch,
+        ServerIsFull,
+        EditorToVanillaMismatch,
+        VanillaToEditorMismatch,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "U8")]
-pub enum TextType {
-    Raw,
-    Chat,
-    Translation,
-    Popup,
-    JukeboxPopup,
-    Tip,
-    System,
-    Whisper,
-    Announcement,
-    ObjectWhisper,
-    Object,
-    ObjectAnnouncement,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum TextType: U8 {
+        Raw,
+        Chat,
+        Translation,
+        Popup,
+        JukeboxPopup,
+        Tip,
+        System,
+        Whisper,
+        Announcement,
+        ObjectWhisper,
+        Object,
+        ObjectAnnouncement,
+        #[default]
+        Invalid,
+    }
 }
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "U8")]
-pub enum InteractAction {
-    #[variant(tag = 3)]
-    LeaveVehicle,
-    MouseOverEntity,
-    NPCOpen,
-    OpenInventory,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum InteractAction: U8 {
+        LeaveVehicle = 3,
+        MouseOverEntity,
+        NPCOpen,
+        OpenInventory,
+        #[default]
+        Invalid,
+    }
 }
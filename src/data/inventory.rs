@@ -1,42 +1,93 @@
-use binary::datatypes::{VarI32, VarU32, VarU64, U8};
+use std::io::{Read, Result, Write};
+
+use binary::{
+    binary::Binary,
+    datatypes::{VarI32, VarU32, VarU64, U8},
+};
 use binary_derive::Binary;
+use bytes::Buf;
+use commons::is_variant;
 
-use super::ByteSlice;
+use crate::{binary_enum, error::Error};
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "VarU32")]
-pub enum InventorySourceType {
-    Invalid = -1,
-    Container,
-    Global,
-    WorldInteraction,
-    Creative,
-    UntrackedInteractionUI,
-    #[default]
-    #[variant(tag = 99999)]
-    NonImplemented,
+use super::{item::ItemInstance, ByteSlice, Position, UBlockPos};
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum InventorySourceType: VarU32 {
+        Invalid = -1,
+        Container,
+        Global,
+        WorldInteraction,
+        Creative,
+        UntrackedInteractionUI = 100,
+        #[default]
+        NonImplemented = 99999,
+    }
 }
 
-#[derive(Debug, Default, Binary)]
-#[data(datatype = "VarU32")]
-pub enum InventorySourceFlag {
-    DropItem,
-    PickupItem,
-    #[default]
-    None,
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum InventorySourceFlag: VarU32 {
+        DropItem,
+        PickupItem,
+        #[default]
+        None,
+    }
 }
 
-#[derive(Debug, Binary, Default)]
+/// InventorySource is the source of an inventory action. Only container sources carry the ID of
+/// the window, and only world interaction sources carry the flags.
+#[derive(Debug, Default)]
 pub struct InventorySource {
     pub source_type: InventorySourceType,
     pub window_id: VarI32,
     pub flags: InventorySourceFlag,
 }
 
+impl Binary for InventorySource {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        self.source_type.serialize(buf);
+
+        if is_variant!(
+            InventorySourceType,
+            self.source_type,
+            Container,
+            NonImplemented
+        ) {
+            self.window_id.serialize(buf);
+        } else if is_variant!(InventorySourceType, self.source_type, WorldInteraction) {
+            self.flags.serialize(buf);
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let source_type = InventorySourceType::deserialize(buf)?;
+        let mut window_id = Default::default();
+        let mut flags = Default::default();
+
+        if is_variant!(InventorySourceType, source_type, Container, NonImplemented) {
+            window_id = VarI32::deserialize(buf)?;
+        } else if is_variant!(InventorySourceType, source_type, WorldInteraction) {
+            flags = InventorySourceFlag::deserialize(buf)?;
+        }
+
+        Ok(Self {
+            source_type,
+            window_id,
+            flags,
+        })
+    }
+}
+
+/// InventoryAction is a change of a single slot of an inventory, from the old item to the new
+/// item.
 #[derive(Debug, Binary, Default)]
 pub struct InventoryAction {
     pub source: InventorySource,
     pub slot: VarU32,
+    pub old_item: ItemInstance,
+    pub new_item: ItemInstance,
 }
 
 #[derive(Debug, Binary, Default)]
@@ -45,27 +96,121 @@ pub struct LegacyItemSlot {
     pub slots: ByteSlice,
 }
 
-#[derive(Binary, Debug, Default)]
-#[data(datatype = "VarU32")]
-#[repr(u32)]
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum UseItemAction: VarU32 {
+        #[default]
+        ClickBlock,
+        ClickAir,
+        BreakBlock,
+    }
+}
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum UseItemOnEntityAction: VarU32 {
+        #[default]
+        Interact,
+        Attack,
+    }
+}
+
+binary_enum! {
+    #[derive(Debug, Default)]
+    pub enum ReleaseItemAction: VarU32 {
+        #[default]
+        Release,
+        Consume,
+    }
+}
+
+/// UseItemTransactionData is sent when the player uses an item on a block or in the air, or breaks
+/// a block.
+#[derive(Debug, Default, Binary)]
+pub struct UseItemTransactionData {
+    pub action_type: UseItemAction,
+    pub block_position: UBlockPos,
+    pub block_face: VarI32,
+    pub hotbar_slot: VarI32,
+    pub held_item: ItemInstance,
+    pub position: Position,
+    pub clicked_position: Position,
+    pub block_runtime_id: VarU32,
+}
+
+/// UseItemOnEntityTransactionData is sent when the player interacts with or attacks an entity.
+#[derive(Debug, Default, Binary)]
+pub struct UseItemOnEntityTransactionData {
+    pub target_runtime_id: VarU64,
+    pub action_type: UseItemOnEntityAction,
+    pub hotbar_slot: VarI32,
+    pub held_item: ItemInstance,
+    pub position: Position,
+    pub clicked_position: Position,
+}
+
+/// ReleaseItemTransactionData is sent when the player releases an item that is being used, such
+/// as a bow, or finishes consuming it.
+#[derive(Debug, Default, Binary)]
+pub struct ReleaseItemTransactionData {
+    pub action_type: ReleaseItemAction,
+    pub hotbar_slot: VarI32,
+    pub held_item: ItemInstance,
+    pub head_position: Position,
+}
+
+/// InventoryTransactionData is the data specific to the type of an inventory transaction. The type
+/// is encoded before the actions of the transaction and the data after them, which is why it
+/// provides its own methods to encode the type and the body separately.
+#[derive(Debug, Default)]
 pub enum InventoryTransactionData {
-    Normal = 0,
-    Mismatch,
-    UseItem {
-        action_type: VarU32,
-        blockface: VarI32,
-        hotbar_slot: VarI32,
-    },
-    UseItemOnEntity {
-        target_runtime_id: VarU64,
-        action_type: VarU32,
-        hotbar_slot: VarI32,
-    },
-    ReleaseItem {
-        action_type: VarU32,
-        hotbar_slot: VarI32,
-        item: VarI32,
-    },
     #[default]
-    Invalid,
+    Normal,
+    Mismatch,
+    UseItem(UseItemTransactionData),
+    UseItemOnEntity(UseItemOnEntityTransactionData),
+    ReleaseItem(ReleaseItemTransactionData),
+}
+
+impl InventoryTransactionData {
+    /// Returns the type of the transaction.
+    pub fn transaction_type(&self) -> u32 {
+        match self {
+            Self::Normal => 0,
+            Self::Mismatch => 1,
+            Self::UseItem(_) => 2,
+            Self::UseItemOnEntity(_) => 3,
+            Self::ReleaseItem(_) => 4,
+        }
+    }
+
+    /// Encodes the data of the transaction, without its type.
+    pub fn serialize_body<W: Write>(&self, buf: &mut W) {
+        match self {
+            Self::Normal | Self::Mismatch => {}
+            Self::UseItem(data) => data.serialize(buf),
+            Self::UseItemOnEntity(data) => data.serialize(buf),
+            Self::ReleaseItem(data) => data.serialize(buf),
+        }
+    }
+
+    /// Decodes the data of a transaction of the provided type.
+    pub fn deserialize_body<R: Read + Buf>(transaction_type: u32, buf: &mut R) -> Result<Self> {
+        let data = match transaction_type {
+            0 => Self::Normal,
+            1 => Self::Mismatch,
+            2 => Self::UseItem(UseItemTransactionData::deserialize(buf)?),
+            3 => Self::UseItemOnEntity(UseItemOnEntityTransactionData::deserialize(buf)?),
+            4 => Self::ReleaseItem(ReleaseItemTransactionData::deserialize(buf)?),
+            value => {
+                let err = Error::InvalidDiscriminant {
+                    ty: "InventoryTransactionData",
+                    value: value as i64,
+                };
+                return Err(err.into());
+            }
+        };
+
+        Ok(data)
+    }
 }
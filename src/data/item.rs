@@ -1,50 +1,218 @@
 use bytes::Buf;
-use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::io::{Read, Result, Write};
 
 use binary::{
     binary::Binary,
-    datatypes::{Bool, VarI32, VarU32, I16, U16},
+    datatypes::{Bool, VarI32, VarU32, I16, I64, U16, U8},
 };
 use binary_derive::Binary;
 use byteorder::LE;
-use nbt::{encoding::LittleEndian, NBTCompound};
+use nbt::{
+    encoding::{LittleEndian, NetworkLittleEndian},
+    NBTCompound,
+};
+
+use crate::error::Error;
 
 use super::{SliceU32, StringUTF, VarString};
 
-#[derive(Default, Debug, Binary)]
+/// This is the marker written in place of the length of the NBT user data of an item, announcing
+/// that the NBT follows the version byte.
+const NBT_USER_DATA_MARKER: i16 = -1;
+
+/// This is the only version of the NBT user data of an item.
+const NBT_USER_DATA_VERSION: u8 = 1;
+
+/// ItemInstance is an item stack along with the network ID the server assigned to it, which is
+/// used to refer to the stack in item stack requests.
+#[derive(Default, Debug)]
 pub struct ItemInstance {
     pub stack_network_id: Option<VarI32>,
     pub stack: ItemStack,
 }
 
+///
+/// Special Serialization and Deserialization of ItemInstance exists because the stack network ID
+/// is encoded in the middle of the item stack, right before its block runtime ID.
+///
+impl Binary for ItemInstance {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        self.stack.write(buf, Some(&self.stack_network_id));
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let (stack, stack_network_id) = ItemStack::read(buf, true)?;
+
+        Ok(Self {
+            stack_network_id,
+            stack,
+        })
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct ItemStack {
     pub item_type: ItemType,
     pub block_runtime_id: VarI32,
     pub count: U16<LE>,
-    pub nbt: NBTCompound<LittleEndian>,
+    pub nbt: Option<NBTCompound<LittleEndian>>,
     pub can_be_placed_on: SliceU32<StringUTF>,
     pub can_break: SliceU32<StringUTF>,
+    /// BlockingTick is the tick at which the shield started blocking. It must be set for the
+    /// shield, as it is only encoded if it is set.
+    pub blocking_tick: Option<I64<LE>>,
 }
 
+///
+/// Special Serialization and Deserialization of ItemStack exists because nothing but the network
+/// ID is encoded for air, and the NBT, the blocks the item can be placed on or break and the
+/// blocking tick of the shield are encoded in a length prefixed blob of extra data. The runtime ID
+/// of the shield is assigned by the server, so the blocking tick is recognised as the exactly 8
+/// bytes of extra data left over after the blocks, whatever the item.
+///
 impl Binary for ItemStack {
     fn serialize<W: Write>(&self, buf: &mut W) {
+        self.write(buf, None);
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        Ok(Self::read(buf, false)?.0)
+    }
+}
+
+impl ItemStack {
+    /// Returns true if the stack is air, in which case nothing but its network ID is encoded.
+    pub fn is_empty(&self) -> bool {
+        self.item_type.network_id.0 == 0
+    }
+
+    /// Returns true if the item is the shield, whose runtime ID is sent in the items of the
+    /// StartGame packet.
+    pub fn is_shield(&self, shield_runtime_id: i32) -> bool {
+        self.item_type.network_id.0 == shield_runtime_id
+    }
+
+    /// Encodes the item stack, along with the stack network ID if it is part of an item instance.
+    fn write<W: Write>(&self, buf: &mut W, stack_network_id: Option<&Option<VarI32>>) {
         self.item_type.network_id.serialize(buf);
 
-        if self.item_type.network_id.0 == 0 {
+        if self.is_empty() {
             return;
         }
 
         self.count.serialize(buf);
         self.item_type.metadata_value.serialize(buf);
+
+        if let Some(stack_network_id) = stack_network_id {
+            Bool::new(stack_network_id.is_some()).serialize(buf);
+            if let Some(stack_network_id) = stack_network_id {
+                stack_network_id.serialize(buf);
+            }
+        }
+
         self.block_runtime_id.serialize(buf);
 
-        self.can_be_placed_on.serialize(buf);
-        self.can_break.serialize(buf);
+        let mut extra = Vec::new();
+        match &self.nbt {
+            Some(nbt) => {
+                I16::<LE>::new(NBT_USER_DATA_MARKER).serialize(&mut extra);
+                U8::new(NBT_USER_DATA_VERSION).serialize(&mut extra);
+                nbt.serialize(&mut extra);
+            }
+            None => I16::<LE>::new(0).serialize(&mut extra),
+        }
+
+        self.can_be_placed_on.serialize(&mut extra);
+        self.can_break.serialize(&mut extra);
+
+        if let Some(tick) = &self.blocking_tick {
+            tick.serialize(&mut extra);
+        }
+
+        VarU32::new(extra.len() as u32).serialize(buf);
+        buf.write_all(&extra).unwrap();
     }
 
-    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
-        Err(Error::new(ErrorKind::Other, ""))
+    /// Decodes the item stack, along with the stack network ID if it is part of an item instance.
+    fn read<R: Read + Buf>(
+        buf: &mut R,
+        with_stack_network_id: bool,
+    ) -> Result<(Self, Option<VarI32>)> {
+        let network_id = VarI32::deserialize(buf)?;
+        if network_id.0 == 0 {
+            return Ok((Self::default(), None));
+        }
+
+        let count = U16::deserialize(buf)?;
+        let metadata_value = VarU32::deserialize(buf)?;
+
+        let mut stack_network_id = None;
+        if with_stack_network_id && Bool::deserialize(buf)?.0 {
+            stack_network_id = Some(VarI32::deserialize(buf)?);
+        }
+
+        let block_runtime_id = VarI32::deserialize(buf)?;
+
+        let len = VarU32::deserialize(buf)?.0 as usize;
+        if len > buf.remaining() {
+            let err = Error::OversizePrefix {
+                len,
+                max: buf.remaining(),
+            };
+            return Err(err.with_field("extra_data").into());
+        }
+
+        let mut extra = vec![0u8; len];
+        buf.read_exact(&mut extra)?;
+        let mut extra = extra.as_slice();
+
+        let nbt = match I16::<LE>::deserialize(&mut extra)?.0 {
+            NBT_USER_DATA_MARKER => {
+                let version = U8::deserialize(&mut extra)?.0;
+                if version != NBT_USER_DATA_VERSION {
+                    let err = Error::InvalidNBT(format!("unknown user data version {}", version));
+                    return Err(err.with_field("nbt").into());
+                }
+
+                let nbt = NBTCompound::deserialize(&mut extra)
+                    .map_err(|err| Error::from_io(err).with_field("nbt"))?;
+                Some(nbt)
+            }
+            0 => None,
+            len => {
+                let err = Error::InvalidNBT(format!("user data of length {} without marker", len));
+                return Err(err.with_field("nbt").into());
+            }
+        };
+
+        let can_be_placed_on = SliceU32::deserialize(&mut extra)
+            .map_err(|err| Error::from_io(err).with_field("can_be_placed_on"))?;
+        let can_break = SliceU32::deserialize(&mut extra)
+            .map_err(|err| Error::from_io(err).with_field("can_break"))?;
+
+        let blocking_tick = match extra.len() {
+            0 => None,
+            8 => Some(I64::deserialize(&mut extra)?),
+            len => {
+                let err = Error::InvalidItem(format!("{} bytes of extra data left", len));
+                return Err(err.with_field("blocking_tick").into());
+            }
+        };
+
+        let stack = Self {
+            item_type: ItemType {
+                network_id,
+                metadata_value,
+            },
+            block_runtime_id,
+            count,
+            nbt,
+            can_be_placed_on,
+            can_break,
+            blocking_tick,
+        };
+
+        Ok((stack, stack_network_id))
     }
 }
 
@@ -60,3 +228,10 @@ pub struct ItemEntry {
     pub runtime_id: I16<LE>,
     pub component_based: Bool,
 }
+
+/// ItemComponentEntry holds the definition of a custom item, as the NBT of its components.
+#[derive(Default, Debug, Binary)]
+pub struct ItemComponentEntry {
+    pub name: VarString,
+    pub data: NBTCompound<NetworkLittleEndian>,
+}
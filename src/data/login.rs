@@ -0,0 +1,299 @@
+use std::io::{Read, Result, Write};
+
+use binary::{binary::Binary, datatypes::VarU32};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use bytes::Buf;
+use p384::SecretKey;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{error::Error, jwt};
+
+/// ConnectionRequest is the data of the Login and SubClientLogin packets. It is composed of the
+/// certificate chain of the player and the client data JWT, which are each prefixed with their
+/// length as a little endian 32-bit integer.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionRequest {
+    pub chain: LoginChain,
+    /// ClientData is the JWT holding the [`ClientData`] claims, signed by the key in the last
+    /// link of the chain.
+    pub client_data: String,
+}
+
+impl ConnectionRequest {
+    /// Decodes the connection request from the data of the Login packet.
+    pub fn decode(mut data: &[u8]) -> crate::error::Result<Self> {
+        let chain = read_string(&mut data)?;
+        let chain =
+            serde_json::from_str(&chain).map_err(|err| Error::InvalidJWT(err.to_string()))?;
+        let client_data = read_string(&mut data)?;
+
+        Ok(Self { chain, client_data })
+    }
+
+    /// Encodes the connection request into the data of the Login packet.
+    pub fn encode(&self) -> Vec<u8> {
+        let chain = serde_json::to_string(&self.chain).unwrap();
+
+        let mut data = Vec::with_capacity(8 + chain.len() + self.client_data.len());
+        write_string(&mut data, &chain);
+        write_string(&mut data, &self.client_data);
+
+        data
+    }
+
+    /// Returns the identity of the player held by the last link of the chain. The chain is not
+    /// verified by this method.
+    pub fn identity_data(&self) -> crate::error::Result<IdentityData> {
+        let token = self
+            .chain
+            .chain
+            .last()
+            .ok_or_else(|| Error::InvalidJWT("chain is empty".into()))?;
+
+        Ok(jwt::decode_claims::<ChainClaims>(token)?.extra_data)
+    }
+
+    /// Returns the client data claims. The signature is not verified by this method.
+    pub fn client_data(&self) -> crate::error::Result<ClientData> {
+        jwt::decode_claims(&self.client_data)
+    }
+
+    /// Creates and returns a self signed connection request, as sent by clients that are not
+    /// authenticated with Xbox Live. Both the single link of the chain and the client data are
+    /// signed by the provided key.
+    pub fn self_signed(
+        identity: IdentityData,
+        client_data: &ClientData,
+        key: &SecretKey,
+    ) -> crate::error::Result<Self> {
+        let claims = ChainClaims {
+            identity_public_key: crate::packet::encryption::encode_public_key(&key.public_key())?,
+            extra_data: identity,
+            ..Default::default()
+        };
+
+        Ok(Self {
+            chain: LoginChain {
+                chain: vec![jwt::sign(&claims, key)?],
+            },
+            client_data: jwt::sign(client_data, key)?,
+        })
+    }
+}
+
+impl Binary for ConnectionRequest {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        let data = self.encode();
+
+        VarU32::new(data.len() as u32).serialize(buf);
+        buf.write_all(&data).unwrap();
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let len = VarU32::deserialize(buf)?.0 as usize;
+        if len > buf.remaining() {
+            return Err(Error::OversizePrefix {
+                len,
+                max: buf.remaining(),
+            }
+            .with_field("connection_request")
+            .into());
+        }
+
+        let mut data = vec![0u8; len];
+        buf.read_exact(&mut data)?;
+
+        Self::decode(&data).map_err(|err| err.with_field("connection_request").into())
+    }
+}
+
+/// LoginChain is the certificate chain of the player. Each link is a JWT signed by the key in the
+/// `identityPublicKey` claim of the previous link.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoginChain {
+    pub chain: Vec<String>,
+}
+
+/// ChainClaims are the claims of a single link of the certificate chain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainClaims {
+    /// IdentityPublicKey is the base64 encoded public key that signed the next link of the chain.
+    pub identity_public_key: String,
+    /// ExtraData is only present in the last link of the chain and holds the identity of the player.
+    #[serde(default)]
+    pub extra_data: IdentityData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_authority: Option<bool>,
+    #[serde(rename = "iss", default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(rename = "exp", default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(rename = "nbf", default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<i64>,
+    #[serde(rename = "iat", default, skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<i64>,
+}
+
+/// IdentityData is the identity of the player, as found in the last link of the certificate chain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdentityData {
+    /// XUID is the Xbox Live user ID of the player. It is empty for players that are not
+    /// authenticated with Xbox Live.
+    #[serde(rename = "XUID", default)]
+    pub xuid: String,
+    #[serde(rename = "displayName", default)]
+    pub display_name: String,
+    /// Identity is the UUID of the player, derived from the display name by the authentication
+    /// servers.
+    #[serde(rename = "identity", default)]
+    pub identity: String,
+    /// TitleID is the ID of the Minecraft edition the player is playing on.
+    #[serde(rename = "titleId", default, skip_serializing_if = "String::is_empty")]
+    pub title_id: String,
+}
+
+/// DeviceOS is the operating system of the device the player is playing on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceOS {
+    Android,
+    IOS,
+    OSX,
+    FireOS,
+    GearVR,
+    Hololens,
+    Win10,
+    Win32,
+    Dedicated,
+    TVOS,
+    Orbis,
+    NX,
+    XBOX,
+    WindowsPhone,
+    Linux,
+    #[default]
+    Invalid,
+}
+
+impl From<i32> for DeviceOS {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Android,
+            2 => Self::IOS,
+            3 => Self::OSX,
+            4 => Self::FireOS,
+            5 => Self::GearVR,
+            6 => Self::Hololens,
+            7 => Self::Win10,
+            8 => Self::Win32,
+            9 => Self::Dedicated,
+            10 => Self::TVOS,
+            11 => Self::Orbis,
+            12 => Self::NX,
+            13 => Self::XBOX,
+            14 => Self::WindowsPhone,
+            15 => Self::Linux,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+/// ClientData holds the data of the client sent in the client data JWT of the Login packet. The
+/// claims that are not modelled by this structure are kept in `extra` so that the data round-trips.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClientData {
+    #[serde(rename = "DeviceOS", default)]
+    pub device_os: i32,
+    #[serde(default)]
+    pub device_model: String,
+    #[serde(default)]
+    pub device_id: String,
+    #[serde(default)]
+    pub client_random_id: i64,
+    #[serde(default)]
+    pub game_version: String,
+    #[serde(default)]
+    pub server_address: String,
+    #[serde(default)]
+    pub language_code: String,
+    #[serde(default)]
+    pub gui_scale: i32,
+    #[serde(rename = "UIProfile", default)]
+    pub ui_profile: i32,
+    #[serde(default)]
+    pub current_input_mode: i32,
+    #[serde(default)]
+    pub default_input_mode: i32,
+    #[serde(default)]
+    pub self_signed_id: String,
+    #[serde(default)]
+    pub third_party_name: String,
+    #[serde(default)]
+    pub play_fab_id: String,
+    #[serde(default)]
+    pub platform_online_id: String,
+    #[serde(default)]
+    pub platform_offline_id: String,
+    #[serde(default)]
+    pub skin_id: String,
+    /// SkinData is the base64 encoded RGBA image data of the skin.
+    #[serde(default)]
+    pub skin_data: String,
+    #[serde(default)]
+    pub skin_image_width: i32,
+    #[serde(default)]
+    pub skin_image_height: i32,
+    #[serde(default)]
+    pub skin_resource_patch: String,
+    #[serde(default)]
+    pub skin_geometry_data: String,
+    #[serde(rename = "SkinColor", default)]
+    pub skin_colour: String,
+    #[serde(default)]
+    pub arm_size: String,
+    #[serde(default)]
+    pub cape_id: String,
+    #[serde(default)]
+    pub cape_data: String,
+    #[serde(default)]
+    pub cape_image_width: i32,
+    #[serde(default)]
+    pub cape_image_height: i32,
+    #[serde(default)]
+    pub premium_skin: bool,
+    #[serde(default)]
+    pub persona_skin: bool,
+    #[serde(default)]
+    pub trusted_skin: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ClientData {
+    /// Returns the operating system of the device the player is playing on.
+    pub fn device_os(&self) -> DeviceOS {
+        DeviceOS::from(self.device_os)
+    }
+}
+
+fn read_string(data: &mut &[u8]) -> crate::error::Result<String> {
+    let len = data.read_u32::<LE>()? as usize;
+    if len > data.len() {
+        return Err(Error::OversizePrefix {
+            len,
+            max: data.len(),
+        });
+    }
+
+    let (string, rest) = data.split_at(len);
+    *data = rest;
+
+    String::from_utf8(string.to_vec()).map_err(|err| Error::InvalidJWT(err.to_string()))
+}
+
+fn write_string(data: &mut Vec<u8>, string: &str) {
+    data.write_u32::<LE>(string.len() as u32).unwrap();
+    data.extend_from_slice(string.as_bytes());
+}
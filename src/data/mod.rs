@@ -10,7 +10,10 @@ use byteorder::LE;
 use bytes::Buf;
 use uuid::Uuid;
 
+use crate::error::Error;
+
 pub mod block;
+pub mod chunk;
 pub mod education;
 pub mod entity;
 pub mod event;
@@ -18,8 +21,10 @@ pub mod game;
 pub mod generic;
 pub mod inventory;
 pub mod item;
+pub mod login;
 pub mod player;
 pub mod resource_pack;
+pub mod stack_request;
 
 /// This is an alias for a String whose prefix is encoded in the format of
 /// an Unsigned VarInt.
@@ -45,6 +50,149 @@ pub type SliceU32<B> = Array<B, U32<LE>>;
 /// 16 bit integer.
 pub type SliceU16<B> = Array<B, U16<LE>>;
 
+/// This is an alias for an array whose prefix is encoded in the format of unsigned
+/// 8 bit integer.
+pub type SliceU8<B> = Array<B, U8>;
+
+/// Checks that a list of the length, whose elements are each at least the size long, fits into the
+/// remaining bytes, which bounds the memory allocated for the list before it is decoded.
+pub(crate) fn check_len(
+    len: usize,
+    size: usize,
+    remaining: usize,
+    field: &'static str,
+) -> Result<()> {
+    match oversize_len(len, size, remaining) {
+        Some(err) => Err(err.with_field(field).into()),
+        None => Ok(()),
+    }
+}
+
+/// Returns the error reported for a list of the length that does not fit into the remaining bytes,
+/// if it does not.
+fn oversize_len(len: usize, size: usize, remaining: usize) -> Option<Error> {
+    if len.saturating_mul(size) > remaining {
+        return Some(Error::OversizePrefix {
+            len,
+            max: remaining / size,
+        });
+    }
+
+    None
+}
+
+/// Declares a list prefixed with its length in the datatype, whose length is checked using
+/// [`check_len`] before the elements are decoded. The const parameter is the smallest encoded size
+/// of an element, which must not be zero.
+macro_rules! checked_list {
+    ($(#[$meta:meta])* $name:ident, $prefix:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name<B, const MIN_SIZE: usize>(pub Vec<B>);
+
+        impl<B, const MIN_SIZE: usize> Default for $name<B, MIN_SIZE> {
+            fn default() -> Self {
+                Self(Vec::new())
+            }
+        }
+
+        impl<B, const MIN_SIZE: usize> From<Vec<B>> for $name<B, MIN_SIZE> {
+            fn from(items: Vec<B>) -> Self {
+                Self(items)
+            }
+        }
+
+        impl<B: Binary, const MIN_SIZE: usize> Binary for $name<B, MIN_SIZE> {
+            fn serialize<W: Write>(&self, buf: &mut W) {
+                <$prefix>::new(self.0.len() as u32).serialize(buf);
+                for item in &self.0 {
+                    item.serialize(buf);
+                }
+            }
+
+            fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+                let len = <$prefix>::deserialize(buf)?.0 as usize;
+                if let Some(err) = oversize_len(len, MIN_SIZE, buf.remaining()) {
+                    return Err(err.into());
+                }
+
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(B::deserialize(buf)?);
+                }
+
+                Ok(Self(items))
+            }
+        }
+    };
+}
+
+checked_list!(
+    /// List is a list prefixed with its length as a VarU32, like [`Slice`], which is used for the
+    /// lists whose length is controlled by the other end of the connection.
+    List,
+    VarU32
+);
+
+checked_list!(
+    /// ListU32 is a list prefixed with its length as a little endian u32, like [`SliceU32`], which
+    /// is used for the lists whose length is controlled by the other end of the connection.
+    ListU32,
+    U32<LE>
+);
+
+/// Declares a fieldless enum that is encoded as its discriminant in the datatype following its name,
+/// and implements [`Binary`] for it. Decoding a value that is not the discriminant of any of the
+/// variants fails with [`Error::InvalidDiscriminant`] naming the enum.
+#[macro_export]
+macro_rules! binary_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident: $datatype:ty {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident $(= $discriminant:expr)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant $(= $discriminant)?,
+            )*
+        }
+
+        impl binary::binary::Binary for $name {
+            fn serialize<W: std::io::Write>(&self, buf: &mut W) {
+                let discriminant = match self {
+                    $(
+                        Self::$variant => Self::$variant as i64,
+                    )*
+                };
+
+                binary::binary::Binary::serialize(&<$datatype>::new(discriminant as _), buf);
+            }
+
+            fn deserialize<R: std::io::Read + bytes::Buf>(buf: &mut R) -> std::io::Result<Self> {
+                let value = <$datatype as binary::binary::Binary>::deserialize(buf)?.0;
+
+                $(
+                    if value == <$datatype>::new(Self::$variant as i64 as _).0 {
+                        return Ok(Self::$variant);
+                    }
+                )*
+
+                let err = $crate::error::Error::InvalidDiscriminant {
+                    ty: stringify!($name),
+                    value: value as i64,
+                };
+                Err(err.into())
+            }
+        }
+    };
+}
+
 /// A position of an entity in a Minecraft World is represented by three
 /// coordinates x, y, and z.
 #[derive(Binary, Debug, Clone, Default)]
@@ -1,6 +1,8 @@
 use binary::datatypes::{Bool, VarI32};
 use binary_derive::Binary;
 
+use crate::binary_enum;
+
 #[derive(Default, Debug, Binary)]
 pub struct PlayerMoveSettings {
     pub movement_type: MovementType,
@@ -8,11 +10,13 @@ pub struct PlayerMoveSettings {
     pub server_authoritative_block_breaking: Bool,
 }
 
-#[derive(Default, Debug, Binary)]
-pub enum MovementType {
-    Client,
-    Server,
-    ServerWithRewind,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum MovementType: VarI32 {
+        Client,
+        Server,
+        ServerWithRewind,
+        #[default]
+        Invalid,
+    }
 }
@@ -1,19 +1,21 @@
-use binary::datatypes::{Bool, U64};
+use binary::datatypes::{Bool, U64, U8};
 use binary_derive::Binary;
 use byteorder::LE;
 
+use crate::binary_enum;
+
 use super::VarString;
 
-#[derive(Default, Debug, Binary)]
-#[data(datatype = "U8")]
-pub enum ResourcePackResponse {
-    #[variant(tag = 1)]
-    Refused,
-    SendPacks,
-    AllPacksDownloaded,
-    Completed,
-    #[default]
-    Invalid,
+binary_enum! {
+    #[derive(Default, Debug)]
+    pub enum ResourcePackResponse: U8 {
+        Refused = 1,
+        SendPacks,
+        AllPacksDownloaded,
+        Completed,
+        #[default]
+        Invalid,
+    }
 }
 
 #[derive(Default, Debug, Binary)]
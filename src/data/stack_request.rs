@@ -0,0 +1,537 @@
+use std::io::{Read, Result, Write};
+
+use binary::{
+    binary::Binary,
+    datatypes::{Bool, VarI32, VarU32, I16, I32, U8},
+};
+use binary_derive::Binary;
+use byteorder::LE;
+use bytes::Buf;
+
+use crate::error::Error;
+
+use super::{item::ItemStack, List, SliceU8, VarString};
+
+/// StackRequest is a request of the client to change the items of its inventories, sent when the
+/// server has enabled server authoritative inventories in the StartGame packet. The actions are
+/// applied in order, and the server answers with a [`StackResponse`] of the same request ID.
+#[derive(Debug, Default, Binary)]
+pub struct StackRequest {
+    pub request_id: VarI32,
+    pub actions: List<StackRequestAction, 1>,
+    pub filter_strings: List<VarString, 1>,
+    pub filter_cause: I32<LE>,
+}
+
+/// StackRequestSlotInfo refers to a slot of a container, along with the network ID of the stack
+/// the client expects to be in it.
+#[derive(Debug, Default, Binary)]
+pub struct StackRequestSlotInfo {
+    pub container_id: U8,
+    pub slot: U8,
+    pub stack_network_id: VarI32,
+}
+
+/// StackRequestAction is a single action of a stack request.
+#[derive(Debug)]
+pub enum StackRequestAction {
+    Take {
+        count: U8,
+        source: StackRequestSlotInfo,
+        destination: StackRequestSlotInfo,
+    },
+    Place {
+        count: U8,
+        source: StackRequestSlotInfo,
+        destination: StackRequestSlotInfo,
+    },
+    Swap {
+        source: StackRequestSlotInfo,
+        destination: StackRequestSlotInfo,
+    },
+    Drop {
+        count: U8,
+        source: StackRequestSlotInfo,
+        randomly: Bool,
+    },
+    Destroy {
+        count: U8,
+        source: StackRequestSlotInfo,
+    },
+    Consume {
+        count: U8,
+        source: StackRequestSlotInfo,
+    },
+    Create {
+        results_slot: U8,
+    },
+    PlaceInContainer {
+        count: U8,
+        source: StackRequestSlotInfo,
+        destination: StackRequestSlotInfo,
+    },
+    TakeOutContainer {
+        count: U8,
+        source: StackRequestSlotInfo,
+        destination: StackRequestSlotInfo,
+    },
+    LabTableCombine,
+    BeaconPayment {
+        primary_effect: VarI32,
+        secondary_effect: VarI32,
+    },
+    MineBlock {
+        hotbar_slot: VarI32,
+        predicted_durability: VarI32,
+        stack_network_id: VarI32,
+    },
+    CraftRecipe {
+        recipe_network_id: VarU32,
+        number_of_crafts: U8,
+    },
+    CraftRecipeAuto {
+        recipe_network_id: VarU32,
+        number_of_crafts: U8,
+        times_crafted: U8,
+        ingredients: SliceU8<RecipeIngredient>,
+    },
+    CraftCreative {
+        creative_item_network_id: VarU32,
+    },
+    CraftRecipeOptional {
+        recipe_network_id: VarU32,
+        filter_string_index: I32<LE>,
+    },
+    CraftGrindstone {
+        recipe_network_id: VarU32,
+        cost: VarI32,
+    },
+    CraftLoom {
+        pattern: VarString,
+    },
+    CraftNonImplementedDeprecated,
+    CraftResultsDeprecated {
+        result_items: List<ItemStack, 1>,
+        times_crafted: U8,
+    },
+}
+
+impl StackRequestAction {
+    /// Returns the ID of the type of the action.
+    pub fn action_type(&self) -> u8 {
+        match self {
+            Self::Take { .. } => 0,
+            Self::Place { .. } => 1,
+            Self::Swap { .. } => 2,
+            Self::Drop { .. } => 3,
+            Self::Destroy { .. } => 4,
+            Self::Consume { .. } => 5,
+            Self::Create { .. } => 6,
+            Self::PlaceInContainer { .. } => 7,
+            Self::TakeOutContainer { .. } => 8,
+            Self::LabTableCombine => 9,
+            Self::BeaconPayment { .. } => 10,
+            Self::MineBlock { .. } => 11,
+            Self::CraftRecipe { .. } => 12,
+            Self::CraftRecipeAuto { .. } => 13,
+            Self::CraftCreative { .. } => 14,
+            Self::CraftRecipeOptional { .. } => 15,
+            Self::CraftGrindstone { .. } => 16,
+            Self::CraftLoom { .. } => 17,
+            Self::CraftNonImplementedDeprecated => 18,
+            Self::CraftResultsDeprecated { .. } => 19,
+        }
+    }
+}
+
+///
+/// Special Serialization and Deserialization of StackRequestAction exists because unknown action
+/// types have to be reported rather than decoded as a default variant, as the rest of the
+/// request could not be decoded anyway.
+///
+impl Binary for StackRequestAction {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        U8::new(self.action_type()).serialize(buf);
+
+        match self {
+            Self::Take {
+                count,
+                source,
+                destination,
+            }
+            | Self::Place {
+                count,
+                source,
+                destination,
+            }
+            | Self::PlaceInContainer {
+                count,
+                source,
+                destination,
+            }
+            | Self::TakeOutContainer {
+                count,
+                source,
+                destination,
+            } => {
+                count.serialize(buf);
+                source.serialize(buf);
+                destination.serialize(buf);
+            }
+            Self::Swap {
+                source,
+                destination,
+            } => {
+                source.serialize(buf);
+                destination.serialize(buf);
+            }
+            Self::Drop {
+                count,
+                source,
+                randomly,
+            } => {
+                count.serialize(buf);
+                source.serialize(buf);
+                randomly.serialize(buf);
+            }
+            Self::Destroy { count, source } | Self::Consume { count, source } => {
+                count.serialize(buf);
+                source.serialize(buf);
+            }
+            Self::Create { results_slot } => results_slot.serialize(buf),
+            Self::LabTableCombine | Self::CraftNonImplementedDeprecated => {}
+            Self::BeaconPayment {
+                primary_effect,
+                secondary_effect,
+            } => {
+                primary_effect.serialize(buf);
+                secondary_effect.serialize(buf);
+            }
+            Self::MineBlock {
+                hotbar_slot,
+                predicted_durability,
+                stack_network_id,
+            } => {
+                hotbar_slot.serialize(buf);
+                predicted_durability.serialize(buf);
+                stack_network_id.serialize(buf);
+            }
+            Self::CraftRecipe {
+                recipe_network_id,
+                number_of_crafts,
+            } => {
+                recipe_network_id.serialize(buf);
+                number_of_crafts.serialize(buf);
+            }
+            Self::CraftRecipeAuto {
+                recipe_network_id,
+                number_of_crafts,
+                times_crafted,
+                ingredients,
+            } => {
+                recipe_network_id.serialize(buf);
+                number_of_crafts.serialize(buf);
+                times_crafted.serialize(buf);
+                ingredients.serialize(buf);
+            }
+            Self::CraftCreative {
+                creative_item_network_id,
+            } => creative_item_network_id.serialize(buf),
+            Self::CraftRecipeOptional {
+                recipe_network_id,
+                filter_string_index,
+            } => {
+                recipe_network_id.serialize(buf);
+                filter_string_index.serialize(buf);
+            }
+            Self::CraftGrindstone {
+                recipe_network_id,
+                cost,
+            } => {
+                recipe_network_id.serialize(buf);
+                cost.serialize(buf);
+            }
+            Self::CraftLoom { pattern } => pattern.serialize(buf),
+            Self::CraftResultsDeprecated {
+                result_items,
+                times_crafted,
+            } => {
+                result_items.serialize(buf);
+                times_crafted.serialize(buf);
+            }
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let action = match U8::deserialize(buf)?.0 {
+            0 => Self::Take {
+                count: Binary::deserialize(buf)?,
+                source: Binary::deserialize(buf)?,
+                destination: Binary::deserialize(buf)?,
+            },
+            1 => Self::Place {
+                count: Binary::deserialize(buf)?,
+                source: Binary::deserialize(buf)?,
+                destination: Binary::deserialize(buf)?,
+            },
+            2 => Self::Swap {
+                source: Binary::deserialize(buf)?,
+                destination: Binary::deserialize(buf)?,
+            },
+            3 => Self::Drop {
+                count: Binary::deserialize(buf)?,
+                source: Binary::deserialize(buf)?,
+                randomly: Binary::deserialize(buf)?,
+            },
+            4 => Self::Destroy {
+                count: Binary::deserialize(buf)?,
+                source: Binary::deserialize(buf)?,
+            },
+            5 => Self::Consume {
+                count: Binary::deserialize(buf)?,
+                source: Binary::deserialize(buf)?,
+            },
+            6 => Self::Create {
+                results_slot: Binary::deserialize(buf)?,
+            },
+            7 => Self::PlaceInContainer {
+                count: Binary::deserialize(buf)?,
+                source: Binary::deserialize(buf)?,
+                destination: Binary::deserialize(buf)?,
+            },
+            8 => Self::TakeOutContainer {
+                count: Binary::deserialize(buf)?,
+                source: Binary::deserialize(buf)?,
+                destination: Binary::deserialize(buf)?,
+            },
+            9 => Self::LabTableCombine,
+            10 => Self::BeaconPayment {
+                primary_effect: Binary::deserialize(buf)?,
+                secondary_effect: Binary::deserialize(buf)?,
+            },
+            11 => Self::MineBlock {
+                hotbar_slot: Binary::deserialize(buf)?,
+                predicted_durability: Binary::deserialize(buf)?,
+                stack_network_id: Binary::deserialize(buf)?,
+            },
+            12 => Self::CraftRecipe {
+                recipe_network_id: Binary::deserialize(buf)?,
+                number_of_crafts: Binary::deserialize(buf)?,
+            },
+            13 => Self::CraftRecipeAuto {
+                recipe_network_id: Binary::deserialize(buf)?,
+                number_of_crafts: Binary::deserialize(buf)?,
+                times_crafted: Binary::deserialize(buf)?,
+                ingredients: Binary::deserialize(buf)?,
+            },
+            14 => Self::CraftCreative {
+                creative_item_network_id: Binary::deserialize(buf)?,
+            },
+            15 => Self::CraftRecipeOptional {
+                recipe_network_id: Binary::deserialize(buf)?,
+                filter_string_index: Binary::deserialize(buf)?,
+            },
+            16 => Self::CraftGrindstone {
+                recipe_network_id: Binary::deserialize(buf)?,
+                cost: Binary::deserialize(buf)?,
+            },
+            17 => Self::CraftLoom {
+                pattern: Binary::deserialize(buf)?,
+            },
+            18 => Self::CraftNonImplementedDeprecated,
+            19 => Self::CraftResultsDeprecated {
+                result_items: Binary::deserialize(buf)?,
+                times_crafted: Binary::deserialize(buf)?,
+            },
+            value => {
+                let err = Error::InvalidDiscriminant {
+                    ty: "StackRequestAction",
+                    value: value as i64,
+                };
+                return Err(err.into());
+            }
+        };
+
+        Ok(action)
+    }
+}
+
+/// RecipeIngredient is an item descriptor along with the number of items it stands for.
+#[derive(Debug, Default, Binary)]
+pub struct RecipeIngredient {
+    pub descriptor: ItemDescriptor,
+    pub count: VarI32,
+}
+
+/// ItemDescriptor describes the items accepted as the ingredient of a recipe.
+#[derive(Debug, Default)]
+pub enum ItemDescriptor {
+    #[default]
+    Invalid,
+    Default {
+        network_id: I16<LE>,
+        metadata: I16<LE>,
+    },
+    MoLang {
+        expression: VarString,
+        version: U8,
+    },
+    ItemTag {
+        tag: VarString,
+    },
+    Deferred {
+        name: VarString,
+        metadata: I16<LE>,
+    },
+    ComplexAlias {
+        name: VarString,
+    },
+}
+
+///
+/// Special Serialization and Deserialization of ItemDescriptor exists because the metadata of the
+/// default descriptor is only encoded if its network ID is not zero.
+///
+impl Binary for ItemDescriptor {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        match self {
+            Self::Invalid => U8::new(0).serialize(buf),
+            Self::Default {
+                network_id,
+                metadata,
+            } => {
+                U8::new(1).serialize(buf);
+                network_id.serialize(buf);
+                if network_id.0 != 0 {
+                    metadata.serialize(buf);
+                }
+            }
+            Self::MoLang {
+                expression,
+                version,
+            } => {
+                U8::new(2).serialize(buf);
+                expression.serialize(buf);
+                version.serialize(buf);
+            }
+            Self::ItemTag { tag } => {
+                U8::new(3).serialize(buf);
+                tag.serialize(buf);
+            }
+            Self::Deferred { name, metadata } => {
+                U8::new(4).serialize(buf);
+                name.serialize(buf);
+                metadata.serialize(buf);
+            }
+            Self::ComplexAlias { name } => {
+                U8::new(5).serialize(buf);
+                name.serialize(buf);
+            }
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let descriptor = match U8::deserialize(buf)?.0 {
+            0 => Self::Invalid,
+            1 => {
+                let network_id = I16::deserialize(buf)?;
+                let mut metadata = Default::default();
+                if network_id.0 != 0 {
+                    metadata = I16::deserialize(buf)?;
+                }
+
+                Self::Default {
+                    network_id,
+                    metadata,
+                }
+            }
+            2 => Self::MoLang {
+                expression: Binary::deserialize(buf)?,
+                version: Binary::deserialize(buf)?,
+            },
+            3 => Self::ItemTag {
+                tag: Binary::deserialize(buf)?,
+            },
+            4 => Self::Deferred {
+                name: Binary::deserialize(buf)?,
+                metadata: Binary::deserialize(buf)?,
+            },
+            5 => Self::ComplexAlias {
+                name: Binary::deserialize(buf)?,
+            },
+            value => {
+                let err = Error::InvalidDiscriminant {
+                    ty: "ItemDescriptor",
+                    value: value as i64,
+                };
+                return Err(err.into());
+            }
+        };
+
+        Ok(descriptor)
+    }
+}
+
+/// StackResponse is the answer of the server to a stack request. If the request succeeded, it
+/// holds the resulting contents of the slots changed by the request.
+#[derive(Debug, Default)]
+pub struct StackResponse {
+    /// Result is zero if the request succeeded. Any other value means that it failed, which servers
+    /// of protocol 618 only report as 1, as the client does not tell the failures apart.
+    pub result: U8,
+    pub request_id: VarI32,
+    pub container_info: List<StackResponseContainerInfo, 2>,
+}
+
+impl StackResponse {
+    /// Returns true if the request succeeded.
+    pub fn succeeded(&self) -> bool {
+        self.result.0 == 0
+    }
+}
+
+///
+/// Special Serialization and Deserialization of StackResponse exists because the container info is
+/// only encoded if the request succeeded.
+///
+impl Binary for StackResponse {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        self.result.serialize(buf);
+        self.request_id.serialize(buf);
+
+        if self.succeeded() {
+            self.container_info.serialize(buf);
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let mut response = Self {
+            result: U8::deserialize(buf)?,
+            request_id: VarI32::deserialize(buf)?,
+            container_info: Default::default(),
+        };
+
+        if response.succeeded() {
+            response.container_info = List::deserialize(buf)?;
+        }
+
+        Ok(response)
+    }
+}
+
+#[derive(Debug, Default, Binary)]
+pub struct StackResponseContainerInfo {
+    pub container_id: U8,
+    pub slots: List<StackResponseSlotInfo, 6>,
+}
+
+/// StackResponseSlotInfo is the resulting content of a slot changed by a stack request.
+#[derive(Debug, Default, Binary)]
+pub struct StackResponseSlotInfo {
+    pub slot: U8,
+    pub hotbar_slot: U8,
+    pub count: U8,
+    pub stack_network_id: VarI32,
+    pub custom_name: VarString,
+    pub durability_correction: VarI32,
+}
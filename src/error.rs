@@ -1,13 +1,227 @@
-use std::{fmt::Debug, result};
+use std::{fmt, io, result};
+
+use crate::packet::phase::Phase;
 
 /// This enum contains variants of errors that could be returned by the API methods
 /// that this library provides.
-#[derive(PartialEq)]
-pub enum Error {}
+///
+/// Since the [`binary::binary::Binary`] trait surfaces failures as [`std::io::Error`], every
+/// variant can be converted into one and recovered back using [`Error::from_io`].
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The Packet ID read from the packet header is not known to this library.
+    UnknownPacket(u32),
+    /// The buffer ended before the value could be fully read.
+    UnexpectedEof,
+    /// An enum discriminant was read that does not correspond to any of the variants
+    /// of the enum.
+    InvalidDiscriminant { ty: &'static str, value: i64 },
+    /// A length prefix exceeds the maximum allowed size of the value.
+    OversizePrefix { len: usize, max: usize },
+    /// The NBT data could not be decoded.
+    InvalidNBT(String),
+    /// The JSON Web Token could not be decoded or verified.
+    InvalidJWT(String),
+    /// The payload of a batch could not be compressed or decompressed.
+    InvalidCompression(String),
+    /// A public or private key could not be decoded.
+    InvalidKey(String),
+    /// The checksum of an encrypted batch does not match its payload.
+    InvalidChecksum,
+    /// A packet was received that may only be sent in the other direction.
+    UnexpectedDirection { packet: &'static str },
+    /// The protocol version of the client is not supported by this library.
+    UnsupportedProtocol(i32),
+    /// A packet was sent that is not expected in the current phase of the connection.
+    UnexpectedPacket { packet: &'static str, phase: Phase },
+    /// The encoded chunk or sub chunk is malformed.
+    InvalidChunk(String),
+    /// A block state could not be registered or decoded.
+    InvalidBlockState(String),
+    /// An item could not be registered.
+    InvalidItem(String),
+    /// The server advertisement sent in an unconnected pong is malformed.
+    InvalidAdvertisement(String),
+    /// A RakNet datagram or message violates the protocol.
+    RakNet(String),
+    /// The other end of the connection did not send anything for too long.
+    Timeout,
+    /// The connection has been closed.
+    Closed,
+    /// Any other I/O error that does not originate from this library.
+    Io(io::ErrorKind, String),
+    /// Wraps an error with the name of the packet and field that failed to decode.
+    Decode {
+        packet: &'static str,
+        field: &'static str,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Recovers the error of this library from an I/O error. Errors that were not produced by
+    /// this library are converted into [`Error::Io`].
+    pub fn from_io(err: io::Error) -> Self {
+        if err.get_ref().map_or(false, |inner| inner.is::<Self>()) {
+            return *err.into_inner().unwrap().downcast::<Self>().unwrap();
+        }
+
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Self::UnexpectedEof,
+            kind => Self::Io(kind, err.to_string()),
+        }
+    }
+
+    /// Wraps the error with the packet and the field that was being decoded when it occurred.
+    /// The context is merged with the one the error already carries: the names it already has are
+    /// kept, so that the innermost field is reported, and the missing ones are filled in.
+    pub fn context(self, packet: &'static str, field: &'static str) -> Self {
+        match self {
+            Self::Decode {
+                packet: inner_packet,
+                field: inner_field,
+                source,
+            } => Self::Decode {
+                packet: if inner_packet.is_empty() {
+                    packet
+                } else {
+                    inner_packet
+                },
+                field: if inner_field.is_empty() {
+                    field
+                } else {
+                    inner_field
+                },
+                source,
+            },
+            err => Self::Decode {
+                packet,
+                field,
+                source: Box::new(err),
+            },
+        }
+    }
+
+    /// Wraps the error with the field that was being decoded when it occurred, keeping the packet
+    /// it may already carry.
+    pub fn with_field(self, field: &'static str) -> Self {
+        self.context("", field)
+    }
+
+    /// Wraps the error with the packet that was being decoded when it occurred, keeping the field
+    /// it may already carry.
+    pub fn with_packet(self, packet: &'static str) -> Self {
+        self.context(packet, "")
+    }
+
+    /// Returns the innermost error, skipping over the packet and field context.
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Decode { source, .. } => source.root(),
+            err => err,
+        }
+    }
+
+    /// Returns a short, stable name of the kind of the error which may be used for logging
+    /// and counting failures.
+    pub fn kind(&self) -> &'static str {
+        match self.root() {
+            Self::UnknownPacket(_) => "unknown_packet",
+            Self::UnexpectedEof => "unexpected_eof",
+            Self::InvalidDiscriminant { .. } => "invalid_discriminant",
+            Self::OversizePrefix { .. } => "oversize_prefix",
+            Self::InvalidNBT(_) => "invalid_nbt",
+            Self::InvalidJWT(_) => "invalid_jwt",
+            Self::InvalidCompression(_) => "invalid_compression",
+            Self::InvalidKey(_) => "invalid_key",
+            Self::InvalidChecksum => "invalid_checksum",
+            Self::UnexpectedDirection { .. } => "unexpected_direction",
+            Self::UnsupportedProtocol(_) => "unsupported_protocol",
+            Self::UnexpectedPacket { .. } => "unexpected_packet",
+            Self::InvalidChunk(_) => "invalid_chunk",
+            Self::InvalidBlockState(_) => "invalid_block_state",
+            Self::InvalidItem(_) => "invalid_item",
+            Self::InvalidAdvertisement(_) => "invalid_advertisement",
+            Self::RakNet(_) => "raknet",
+            Self::Timeout => "timeout",
+            Self::Closed => "closed",
+            Self::Io(..) => "io",
+            Self::Decode { .. } => unreachable!(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPacket(id) => write!(f, "Packet ID {} not found", id),
+            Self::UnexpectedEof => write!(f, "unexpected end of buffer"),
+            Self::InvalidDiscriminant { ty, value } => {
+                write!(f, "invalid discriminant {} for {}", value, ty)
+            }
+            Self::OversizePrefix { len, max } => {
+                write!(f, "length prefix {} exceeds the maximum of {}", len, max)
+            }
+            Self::InvalidNBT(msg) => write!(f, "invalid NBT: {}", msg),
+            Self::InvalidJWT(msg) => write!(f, "invalid JWT: {}", msg),
+            Self::InvalidCompression(msg) => write!(f, "invalid compression: {}", msg),
+            Self::InvalidKey(msg) => write!(f, "invalid key: {}", msg),
+            Self::InvalidChecksum => write!(f, "checksum of the encrypted batch does not match"),
+            Self::UnexpectedDirection { packet } => {
+                write!(f, "{} may not be sent in this direction", packet)
+            }
+            Self::UnsupportedProtocol(protocol) => {
+                write!(f, "protocol version {} is not supported", protocol)
+            }
+            Self::UnexpectedPacket { packet, phase } => {
+                write!(f, "{} is not expected in the {} phase", packet, phase)
+            }
+            Self::InvalidChunk(msg) => write!(f, "invalid chunk: {}", msg),
+            Self::InvalidBlockState(msg) => write!(f, "invalid block state: {}", msg),
+            Self::InvalidItem(msg) => write!(f, "invalid item: {}", msg),
+            Self::InvalidAdvertisement(msg) => write!(f, "invalid server advertisement: {}", msg),
+            Self::RakNet(msg) => write!(f, "raknet: {}", msg),
+            Self::Timeout => write!(f, "connection timed out"),
+            Self::Closed => write!(f, "connection closed"),
+            Self::Io(kind, msg) => write!(f, "{:?}: {}", kind, msg),
+            Self::Decode {
+                packet: "",
+                field,
+                source,
+            } => write!(f, "{}: {}", field, source),
+            Self::Decode {
+                packet,
+                field: "",
+                source,
+            } => write!(f, "{}: {}", packet, source),
+            Self::Decode {
+                packet,
+                field,
+                source,
+            } => write!(f, "{}.{}: {}", packet, field, source),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err.root() {
+            Error::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+            Error::Io(kind, _) => *kind,
+            Error::Timeout => io::ErrorKind::TimedOut,
+            Error::Closed => io::ErrorKind::ConnectionAborted,
+            _ => io::ErrorKind::InvalidData,
+        };
+
+        io::Error::new(kind, err)
+    }
+}
 
-impl Debug for Error {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::from_io(err)
     }
 }
 
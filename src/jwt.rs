@@ -0,0 +1,105 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use p384::{
+    ecdsa::{signature::Signer, Signature, SigningKey},
+    SecretKey,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    packet::encryption::encode_public_key,
+};
+
+/// This is the only algorithm used to sign the JWTs exchanged over a Minecraft Connection.
+pub const ALGORITHM: &str = "ES384";
+
+/// JWTHeader is the header of a JSON Web Token. The `x5u` field holds the base64 encoded public
+/// key of the key pair that signed the token.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JWTHeader {
+    pub alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5u: Option<String>,
+}
+
+/// JWT is a JSON Web Token split into its three parts. The signature is not verified when decoding
+/// the token.
+#[derive(Debug, Clone)]
+pub struct JWT<'a> {
+    pub header: JWTHeader,
+    /// The header and the payload as they were encoded, which is the input of the signature.
+    pub signed: &'a str,
+    payload: &'a str,
+    signature: &'a str,
+}
+
+impl<'a> JWT<'a> {
+    /// Splits the token into its parts and decodes the header.
+    pub fn decode(token: &'a str) -> Result<Self> {
+        let mut parts = token.splitn(3, '.');
+        let (header, payload, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(header), Some(payload), Some(signature)) => (header, payload, signature),
+            _ => return Err(Error::InvalidJWT("token must have three parts".into())),
+        };
+
+        let header = decode_json(header)?;
+        let signed = &token[..token.len() - signature.len() - 1];
+
+        Ok(Self {
+            header,
+            signed,
+            payload,
+            signature,
+        })
+    }
+
+    /// Decodes the claims of the token without verifying the signature.
+    pub fn claims<T: DeserializeOwned>(&self) -> Result<T> {
+        decode_json(self.payload)
+    }
+
+    /// Decodes the ES384 signature of the token.
+    pub fn signature(&self) -> Result<Signature> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(self.signature)
+            .map_err(|err| Error::InvalidJWT(err.to_string()))?;
+
+        Signature::from_slice(&bytes).map_err(|err| Error::InvalidJWT(err.to_string()))
+    }
+}
+
+/// Decodes the claims of the token without verifying the signature.
+pub fn decode_claims<T: DeserializeOwned>(token: &str) -> Result<T> {
+    JWT::decode(token)?.claims()
+}
+
+/// Encodes the claims into a token signed by the private key using ES384. The public key of the
+/// key pair is put into the `x5u` header.
+pub fn sign<T: Serialize>(claims: &T, key: &SecretKey) -> Result<String> {
+    let header = JWTHeader {
+        alg: ALGORITHM.into(),
+        x5u: Some(encode_public_key(&key.public_key())?),
+    };
+
+    let signed = format!("{}.{}", encode_json(&header)?, encode_json(claims)?);
+    let signature: Signature = SigningKey::from(key).sign(signed.as_bytes());
+
+    Ok(format!(
+        "{}.{}",
+        signed,
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    ))
+}
+
+fn decode_json<T: DeserializeOwned>(part: &str) -> Result<T> {
+    let json = URL_SAFE_NO_PAD
+        .decode(part.trim_end_matches('='))
+        .map_err(|err| Error::InvalidJWT(err.to_string()))?;
+
+    serde_json::from_slice(&json).map_err(|err| Error::InvalidJWT(err.to_string()))
+}
+
+fn encode_json<T: Serialize>(value: &T) -> Result<String> {
+    let json = serde_json::to_vec(value).map_err(|err| Error::InvalidJWT(err.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
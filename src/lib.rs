@@ -1,6 +1,12 @@
+pub mod auth;
+pub mod cache;
 pub mod data;
 pub mod error;
+pub mod jwt;
 pub mod packet;
+pub mod protocol;
+pub mod raknet;
+pub mod registry;
 
 /// This is the latest Minecraft Protocol Version supported by this library.
 pub const PROTOCOL_VERSION: u16 = 618;
@@ -0,0 +1,164 @@
+use std::io::{Read, Result, Write};
+
+use binary::{binary::Binary, datatypes::VarU32};
+use bytes::Buf;
+
+use crate::{error::Error, PROTOCOL_VERSION};
+
+use super::{GamePacket, Packet};
+
+/// This is the ID of the game packet which wraps all the Minecraft Packets sent over
+/// a RakNet connection.
+pub const GAME_PACKET_ID: u8 = 0xfe;
+
+/// Batch represents a single game packet frame. A frame is composed of the game packet ID
+/// followed by Minecraft Packets that are each prefixed with their length as VarU32.
+#[derive(Debug, Default)]
+pub struct Batch {
+    pub packets: Vec<GamePacket>,
+}
+
+impl Batch {
+    /// Creates and returns a new empty Batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the packet to the batch.
+    pub fn push(&mut self, packet: impl Into<GamePacket>) {
+        self.packets.push(packet.into());
+    }
+
+    /// Returns the number of packets in the batch.
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    /// Returns true if there are no packets in the batch.
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Serializes the length-prefixed packets of the batch without the game packet ID. This is the
+    /// part of the frame that is compressed and encrypted.
+    pub fn serialize_payload<W: Write>(&self, buf: &mut W) {
+        self.serialize_payload_versioned(buf, PROTOCOL_VERSION);
+    }
+
+    /// Serializes the length-prefixed packets of the batch without the game packet ID, in the
+    /// provided protocol version.
+    pub fn serialize_payload_versioned<W: Write>(&self, buf: &mut W, protocol: u16) {
+        let mut packet_buf = Vec::new();
+
+        for packet in &self.packets {
+            packet_buf.clear();
+            packet.write_versioned(&mut packet_buf, protocol);
+
+            VarU32::new(packet_buf.len() as u32).serialize(buf);
+            buf.write_all(&packet_buf).unwrap();
+        }
+    }
+
+    /// Returns an iterator over the length-prefixed packets of the payload. A malformed packet
+    /// only yields an error for that packet and the iteration continues with the next one.
+    pub fn read_payload(payload: &[u8]) -> BatchReader<'_> {
+        BatchReader {
+            buf: payload,
+            protocol: PROTOCOL_VERSION,
+            done: false,
+        }
+    }
+
+    /// Returns an iterator over the packets of the frame after checking the game packet ID.
+    pub fn read_frame(frame: &[u8]) -> crate::error::Result<BatchReader<'_>> {
+        Ok(Self::read_payload(frame_payload(frame)?))
+    }
+}
+
+/// Returns the part of the frame after the game packet ID, failing if the frame does not start
+/// with the game packet ID.
+pub fn frame_payload(frame: &[u8]) -> crate::error::Result<&[u8]> {
+    match frame.split_first() {
+        Some((&GAME_PACKET_ID, payload)) => Ok(payload),
+        Some((&id, _)) => Err(Error::InvalidDiscriminant {
+            ty: "GamePacketID",
+            value: id as i64,
+        }),
+        None => Err(Error::UnexpectedEof),
+    }
+}
+
+impl From<Vec<Packet>> for Batch {
+    fn from(packets: Vec<Packet>) -> Self {
+        Self {
+            packets: packets.into_iter().map(GamePacket::new).collect(),
+        }
+    }
+}
+
+impl Binary for Batch {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        buf.write_all(&[GAME_PACKET_ID]).unwrap();
+        self.serialize_payload(buf);
+    }
+
+    ///
+    /// Deserialization of a Batch fails on the first malformed packet. Use [`Batch::read_frame`]
+    /// to skip over malformed packets instead.
+    ///
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let mut frame = Vec::with_capacity(buf.remaining());
+        buf.read_to_end(&mut frame)?;
+
+        let packets = Self::read_frame(&frame)?.collect::<crate::error::Result<_>>()?;
+        Ok(Self { packets })
+    }
+}
+
+/// BatchReader iterates over the length-prefixed packets of a batch payload.
+pub struct BatchReader<'a> {
+    buf: &'a [u8],
+    protocol: u16,
+    done: bool,
+}
+
+impl<'a> BatchReader<'a> {
+    /// Sets the protocol version in which the packets are decoded.
+    pub fn with_protocol(mut self, protocol: u16) -> Self {
+        self.protocol = protocol;
+        self
+    }
+}
+
+impl<'a> Iterator for BatchReader<'a> {
+    type Item = crate::error::Result<GamePacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.buf.is_empty() {
+            return None;
+        }
+
+        let len = match VarU32::deserialize(&mut self.buf) {
+            Ok(len) => len.0 as usize,
+            Err(err) => {
+                self.done = true;
+                return Some(Err(Error::from_io(err)));
+            }
+        };
+
+        // A length prefix running past the end of the frame means that the boundaries of the
+        // remaining packets can not be trusted either.
+        if len > self.buf.len() {
+            self.done = true;
+            return Some(Err(Error::OversizePrefix {
+                len,
+                max: self.buf.len(),
+            }));
+        }
+
+        let (mut packet, rest) = self.buf.split_at(len);
+        self.buf = rest;
+
+        Some(GamePacket::read_versioned(&mut packet, self.protocol).map_err(Error::from_io))
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+use tokio_bytes::{BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{error::Error, protocol::is_supported, PROTOCOL_VERSION};
+
+use super::{
+    batch::{frame_payload, Batch, GAME_PACKET_ID},
+    compression::Compression,
+    encryption::Encryption,
+    GamePacket, Packet,
+};
+
+/// GameCodec encodes and decodes the game packet frames of a Minecraft Connection. Frames carry no
+/// length of their own, so it may only be used with a message oriented transport, such as RakNet,
+/// which delimits the frames: [`GameCodec::decode_frame`] takes one message at a time, and the
+/// [`Decoder`] implementation treats every buffer it is passed as exactly one message.
+///
+/// The frames are compressed once the NetworkSettings packet has been sent or received, which is
+/// done automatically by the codec. The encryption has to be enabled using
+/// [`GameCodec::enable_encryption`] once the handshake completes.
+pub struct GameCodec {
+    compression: Compression,
+    encryption: Option<Encryption>,
+    protocol: u16,
+    pending: VecDeque<Result<GamePacket, Error>>,
+}
+
+impl Default for GameCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameCodec {
+    /// Creates and returns a new GameCodec for a connection that has just been opened.
+    pub fn new() -> Self {
+        Self {
+            compression: Compression::disabled(),
+            encryption: None,
+            protocol: PROTOCOL_VERSION,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns the protocol version in which the packets are encoded and decoded.
+    pub fn protocol(&self) -> u16 {
+        self.protocol
+    }
+
+    /// Sets the protocol version in which the packets are encoded and decoded, as negotiated by the
+    /// RequestNetworkSettings packet, failing if the protocol version is not supported.
+    pub fn set_protocol(&mut self, protocol: u16) -> Result<(), Error> {
+        if !is_supported(protocol) {
+            return Err(Error::UnsupportedProtocol(protocol as i32));
+        }
+
+        self.protocol = protocol;
+        Ok(())
+    }
+
+    /// Enables the compression of the frames encoded and decoded after this call.
+    pub fn enable_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Enables the encryption of the frames encoded and decoded after this call.
+    pub fn enable_encryption(&mut self, encryption: Encryption) {
+        self.encryption = Some(encryption);
+    }
+
+    /// Returns true if the encryption has been enabled.
+    pub fn encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Enables the compression negotiated by the packets of the frame, if any.
+    fn negotiate<'a>(&mut self, mut packets: impl Iterator<Item = &'a GamePacket>) {
+        let settings = packets.find_map(|packet| match &packet.packet {
+            Packet::NetworkSettings(settings) => Some(settings),
+            _ => None,
+        });
+
+        if let Some(settings) = settings {
+            self.compression = Compression::from_settings(settings);
+        }
+    }
+}
+
+impl GameCodec {
+    /// Decodes a frame received as a single message of the transport. Failures of the frame as a
+    /// whole, such as a checksum or decompression failure, are returned as an error. The packets of
+    /// the frame are decoded independently, so a malformed packet is returned as an error in place
+    /// of the packet, leaving it to the caller to skip it or not.
+    pub fn decode_frame(&mut self, frame: &[u8]) -> Result<Vec<Result<GamePacket, Error>>, Error> {
+        let mut payload = frame_payload(frame)?.to_vec();
+
+        if let Some(encryption) = &mut self.encryption {
+            payload = encryption.decrypt(&payload)?;
+        }
+
+        let payload = self.compression.decompress(&payload)?;
+        let packets: Vec<_> = Batch::read_payload(&payload)
+            .with_protocol(self.protocol)
+            .collect();
+
+        self.negotiate(packets.iter().filter_map(|packet| packet.as_ref().ok()));
+        Ok(packets)
+    }
+}
+
+///
+/// The decoder takes the whole buffer as one frame, which holds as long as the transport passes a
+/// single message per call, as UdpFramed does. Byte stream transports are not supported, as frames
+/// buffered together would be decoded as one.
+///
+impl Decoder for GameCodec {
+    type Item = Result<GamePacket, Error>;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(packet) = self.pending.pop_front() {
+            return Ok(Some(packet));
+        }
+
+        if src.is_empty() {
+            return Ok(None);
+        }
+
+        let frame = src.split();
+        self.pending = self.decode_frame(&frame)?.into();
+
+        Ok(self.pending.pop_front())
+    }
+}
+
+impl Encoder<Batch> for GameCodec {
+    type Error = Error;
+
+    fn encode(&mut self, batch: Batch, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut payload = Vec::new();
+        batch.serialize_payload_versioned(&mut payload, self.protocol);
+
+        let mut payload = self.compression.compress(&payload)?;
+        if let Some(encryption) = &mut self.encryption {
+            payload = encryption.encrypt(&payload);
+        }
+
+        dst.reserve(payload.len() + 1);
+        dst.put_u8(GAME_PACKET_ID);
+        dst.put_slice(&payload);
+
+        // The NetworkSettings packet itself is sent uncompressed, every frame after it is not.
+        self.negotiate(batch.packets.iter());
+        Ok(())
+    }
+}
+
+impl Encoder<GamePacket> for GameCodec {
+    type Error = Error;
+
+    fn encode(&mut self, packet: GamePacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let batch = Batch {
+            packets: vec![packet],
+        };
+
+        Encoder::<Batch>::encode(self, batch, dst)
+    }
+}
+
+impl Encoder<Packet> for GameCodec {
+    type Error = Error;
+
+    fn encode(&mut self, packet: Packet, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        Encoder::<GamePacket>::encode(self, GamePacket::new(packet), dst)
+    }
+}
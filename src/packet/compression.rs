@@ -0,0 +1,162 @@
+use std::io::{Read, Write};
+
+use binary::datatypes::{Bool, F32, U16, U8};
+use byteorder::LE;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder};
+
+use crate::{
+    binary_enum,
+    error::{Error, Result},
+};
+
+use super::prelogin::NetworkSettings;
+
+/// This is the maximum size of a decompressed batch. Batches that decompress to a larger size
+/// are rejected to protect against decompression bombs.
+pub const MAX_DECOMPRESSED_SIZE: usize = 8 * 1024 * 1024;
+
+binary_enum! {
+    /// CompressionAlgorithm is the algorithm that is used to compress the batches sent over
+    /// a Minecraft Connection. It is negotiated using the NetworkSettings packet.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum CompressionAlgorithm: U16<LE> {
+        /// Flate compresses the batches using raw deflate.
+        Flate,
+        /// Snappy compresses the batches using the raw snappy block format.
+        Snappy,
+        #[default]
+        None = 65535,
+    }
+}
+
+impl CompressionAlgorithm {
+    /// Compresses the data using the algorithm.
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Flate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            Self::Snappy => snap::raw::Encoder::new()
+                .compress_vec(data)
+                .map_err(|err| Error::InvalidCompression(err.to_string())),
+            Self::None => Ok(data.to_vec()),
+        }
+    }
+
+    /// Decompresses the data using the algorithm. The decompressed data may not be larger than
+    /// [`MAX_DECOMPRESSED_SIZE`].
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let decompressed = match self {
+            Self::Flate => {
+                let mut decompressed = Vec::new();
+                DeflateDecoder::new(data)
+                    .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+                    .read_to_end(&mut decompressed)
+                    .map_err(|err| Error::InvalidCompression(err.to_string()))?;
+                decompressed
+            }
+            Self::Snappy => {
+                let len = snap::raw::decompress_len(data)
+                    .map_err(|err| Error::InvalidCompression(err.to_string()))?;
+
+                if len > MAX_DECOMPRESSED_SIZE {
+                    return Err(Error::OversizePrefix {
+                        len,
+                        max: MAX_DECOMPRESSED_SIZE,
+                    });
+                }
+
+                snap::raw::Decoder::new()
+                    .decompress_vec(data)
+                    .map_err(|err| Error::InvalidCompression(err.to_string()))?
+            }
+            Self::None => data.to_vec(),
+        };
+
+        if decompressed.len() > MAX_DECOMPRESSED_SIZE {
+            return Err(Error::OversizePrefix {
+                len: decompressed.len(),
+                max: MAX_DECOMPRESSED_SIZE,
+            });
+        }
+
+        Ok(decompressed)
+    }
+}
+
+/// Compression compresses and decompresses the payloads of the batches exchanged over a Minecraft
+/// Connection once the NetworkSettings packet has been sent or received.
+///
+/// The supported protocol versions predate the byte identifying the algorithm of each batch, which
+/// was introduced in protocol 649. Every payload is therefore compressed using the negotiated
+/// algorithm, whatever its size, as there is no way to mark a payload as uncompressed.
+#[derive(Debug, Clone)]
+pub struct Compression {
+    algorithm: CompressionAlgorithm,
+    threshold: u16,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl Compression {
+    /// Creates and returns a new Compression using the algorithm. The threshold is only announced
+    /// to the client in the NetworkSettings packet.
+    pub fn new(algorithm: CompressionAlgorithm, threshold: u16) -> Self {
+        Self {
+            algorithm,
+            threshold,
+        }
+    }
+
+    /// Returns the Compression used before the network settings are negotiated, in which
+    /// the batches are not compressed.
+    pub fn disabled() -> Self {
+        Self::new(CompressionAlgorithm::None, 0)
+    }
+
+    /// Returns the Compression negotiated by the NetworkSettings packet. This is used by the client
+    /// once it receives the packet, and by the server right after sending it.
+    pub fn from_settings(settings: &NetworkSettings) -> Self {
+        Self::new(
+            settings.compression_algorithm,
+            settings.compression_threshold.0,
+        )
+    }
+
+    /// Returns the NetworkSettings packet that the server sends to negotiate the Compression.
+    pub fn settings(&self) -> NetworkSettings {
+        NetworkSettings {
+            compression_threshold: U16::new(self.threshold),
+            compression_algorithm: self.algorithm,
+            client_throttle: Bool::new(false),
+            client_throttle_threshold: U8::new(0),
+            client_throttle_scalar: F32::new(0.0),
+        }
+    }
+
+    /// Returns the algorithm used to compress the payloads.
+    pub fn algorithm(&self) -> CompressionAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the minimum size of a payload that is compressed.
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// Compresses the payload of a batch using the negotiated algorithm.
+    pub fn compress(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        self.algorithm.compress(payload)
+    }
+
+    /// Decompresses the payload of a batch using the negotiated algorithm.
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.algorithm.decompress(data)
+    }
+}
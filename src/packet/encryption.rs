@@ -0,0 +1,192 @@
+use aes::Aes256;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use p384::{
+    ecdh::diffie_hellman,
+    pkcs8::{DecodePublicKey, EncodePublicKey},
+    PublicKey, SecretKey,
+};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+
+use super::batch::{frame_payload, GAME_PACKET_ID};
+
+/// This is the length of the salt sent by the server in the S2CHandshake packet.
+pub const SALT_LENGTH: usize = 16;
+
+/// This is the length of the checksum appended to every encrypted batch.
+pub const CHECKSUM_LENGTH: usize = 8;
+
+/// The batches are encrypted using AES-256-GCM without the authentication tag, which boils down
+/// to AES-256-CTR with a 32-bit big endian counter starting at 2.
+type Aes256Ctr = ctr::Ctr32BE<Aes256>;
+
+/// Generates a random salt that is sent to the client in the S2CHandshake packet.
+pub fn generate_salt() -> [u8; SALT_LENGTH] {
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+/// Decodes a public key from the base64 encoded DER format used in the `x5u` header and the
+/// `identityPublicKey` claim of the JWTs.
+pub fn parse_public_key(encoded: &str) -> Result<PublicKey> {
+    let der = STANDARD
+        .decode(encoded)
+        .map_err(|err| Error::InvalidKey(err.to_string()))?;
+
+    PublicKey::from_public_key_der(&der).map_err(|err| Error::InvalidKey(err.to_string()))
+}
+
+/// Encodes the public key into the base64 encoded DER format used in the `x5u` header and the
+/// `identityPublicKey` claim of the JWTs.
+pub fn encode_public_key(key: &PublicKey) -> Result<String> {
+    let der = key
+        .to_public_key_der()
+        .map_err(|err| Error::InvalidKey(err.to_string()))?;
+
+    Ok(STANDARD.encode(der.as_bytes()))
+}
+
+/// Computes the ECDH shared secret between the local private key and the public key of the
+/// remote end of the connection.
+pub fn shared_secret(local: &SecretKey, remote: &PublicKey) -> [u8; 48] {
+    let secret = diffie_hellman(local.to_nonzero_scalar(), remote.as_affine());
+
+    let mut bytes = [0u8; 48];
+    bytes.copy_from_slice(secret.raw_secret_bytes());
+    bytes
+}
+
+/// Derives the key used for the encryption from the salt and the ECDH shared secret.
+pub fn derive_key(salt: &[u8], shared_secret: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(shared_secret);
+    hasher.finalize().into()
+}
+
+/// Encryption encrypts and decrypts the batches exchanged over a Minecraft Connection once the
+/// S2CHandshake packet has been sent or received. Each direction has its own cipher and counter,
+/// which is why a single Encryption must be used for all the batches of a connection in the
+/// order they are sent and received.
+pub struct Encryption {
+    key: [u8; 32],
+    send_cipher: Aes256Ctr,
+    recv_cipher: Aes256Ctr,
+    send_counter: u64,
+    recv_counter: u64,
+    /// Poisoned is set once a batch fails the verification of its checksum, after which the
+    /// receiving cipher no longer matches the one of the remote end.
+    poisoned: bool,
+}
+
+impl Encryption {
+    /// Creates and returns a new Encryption using the derived key.
+    pub fn new(key: [u8; 32]) -> Self {
+        let mut iv = [0u8; 16];
+        iv[..12].copy_from_slice(&key[..12]);
+        iv[15] = 2;
+
+        Self {
+            key,
+            send_cipher: Aes256Ctr::new(&key.into(), &iv.into()),
+            recv_cipher: Aes256Ctr::new(&key.into(), &iv.into()),
+            send_counter: 0,
+            recv_counter: 0,
+            poisoned: false,
+        }
+    }
+
+    /// Creates and returns a new Encryption from the local private key, the public key of the
+    /// remote end of the connection and the salt of the S2CHandshake packet.
+    pub fn from_keys(local: &SecretKey, remote: &PublicKey, salt: &[u8]) -> Self {
+        Self::new(derive_key(salt, &shared_secret(local, remote)))
+    }
+
+    /// Returns the number of batches encrypted so far.
+    pub fn send_counter(&self) -> u64 {
+        self.send_counter
+    }
+
+    /// Returns the number of batches decrypted so far.
+    pub fn recv_counter(&self) -> u64 {
+        self.recv_counter
+    }
+
+    /// Returns true if a batch failed the verification of its checksum, in which case no further
+    /// batch can be decrypted and the connection must be closed.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Encrypts the payload of a batch after appending its checksum.
+    pub fn encrypt(&mut self, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(payload.len() + CHECKSUM_LENGTH);
+        data.extend_from_slice(payload);
+        data.extend_from_slice(&self.checksum(self.send_counter, payload));
+
+        self.send_counter += 1;
+        self.send_cipher.apply_keystream(&mut data);
+
+        data
+    }
+
+    /// Decrypts the payload of a batch and verifies its checksum. The keystream has already been
+    /// advanced when the checksum is verified, so a failure is fatal: every later call fails too.
+    pub fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        if self.poisoned {
+            return Err(Error::InvalidChecksum);
+        }
+        if data.len() < CHECKSUM_LENGTH {
+            return Err(Error::UnexpectedEof);
+        }
+
+        let mut data = data.to_vec();
+        self.recv_cipher.apply_keystream(&mut data);
+
+        let checksum = data.split_off(data.len() - CHECKSUM_LENGTH);
+        if checksum != self.checksum(self.recv_counter, &data) {
+            self.poisoned = true;
+            return Err(Error::InvalidChecksum);
+        }
+
+        self.recv_counter += 1;
+        Ok(data)
+    }
+
+    /// Encrypts the batch frame, leaving the game packet ID in front untouched.
+    pub fn encrypt_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>> {
+        let payload = frame_payload(frame)?;
+
+        let mut encrypted = vec![GAME_PACKET_ID];
+        encrypted.extend(self.encrypt(payload));
+
+        Ok(encrypted)
+    }
+
+    /// Decrypts the batch frame, leaving the game packet ID in front untouched.
+    pub fn decrypt_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>> {
+        let payload = frame_payload(frame)?;
+
+        let mut decrypted = vec![GAME_PACKET_ID];
+        decrypted.extend(self.decrypt(payload)?);
+
+        Ok(decrypted)
+    }
+
+    /// Computes the checksum of the payload which is the first 8 bytes of the SHA-256 hash of the
+    /// counter, the payload and the key.
+    fn checksum(&self, counter: u64, payload: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+        let mut hasher = Sha256::new();
+        hasher.update(counter.to_le_bytes());
+        hasher.update(payload);
+        hasher.update(self.key);
+
+        let mut checksum = [0u8; CHECKSUM_LENGTH];
+        checksum.copy_from_slice(&hasher.finalize()[..CHECKSUM_LENGTH]);
+        checksum
+    }
+}
@@ -31,6 +31,30 @@ impl PacketHeader {
     pub fn id(&self) -> u32 {
         self.id
     }
+
+    /// Returns the ID of the sub client that sent the packet. The primary client has the ID 0.
+    pub fn sender_subclient_id(&self) -> u8 {
+        self.sender_subclient_id
+    }
+
+    /// Returns the ID of the sub client that the packet is meant for. The primary client has the ID 0.
+    pub fn target_subclient_id(&self) -> u8 {
+        self.target_subclient_id
+    }
+
+    /// Sets the ID of the sub client that sent the packet. Only the lower two bits are used
+    /// as there may be at most four clients on a single connection.
+    pub fn with_sender_subclient_id(mut self, subclient_id: u8) -> Self {
+        self.sender_subclient_id = subclient_id & SUBCLIENT_ID_MASK as u8;
+        self
+    }
+
+    /// Sets the ID of the sub client that the packet is meant for. Only the lower two bits are used
+    /// as there may be at most four clients on a single connection.
+    pub fn with_target_subclient_id(mut self, subclient_id: u8) -> Self {
+        self.target_subclient_id = subclient_id & SUBCLIENT_ID_MASK as u8;
+        self
+    }
 }
 
 impl Binary for PacketHeader {
@@ -1,21 +1,32 @@
 #![allow(non_snake_case)]
 
-use crate::packet::header::PacketHeader;
+use crate::{
+    error::Error, packet::header::PacketHeader, protocol::VersionedBinary, PROTOCOL_VERSION,
+};
 use binary::binary::Binary;
 use bytes::Buf;
-use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::io::{Read, Result, Write};
 
 use self::{
     play::{
-        ActorEvent, AddPainting, AddPlayer, BlockEvent, C2SHandshake, Disconnect, LevelEvent,
-        Login, MobEffect, MoveActorAbsolute, MovePlayer, PassengerJump, PlayStatus,
-        ResourcePackClientResponse, ResourcePackStack, ResourcePacksInfo, S2CHandshake, SetTime,
-        StartGame, TakeItemActor, Text, TickSync, UpdateAttributes, UpdateBlock,
+        ActorEvent, AddActor, AddItemActor, AddPainting, AddPlayer, AvailableActorIdentifiers,
+        BlockEvent, C2SHandshake, ClientCacheBlobStatus, ClientCacheMissResponse,
+        ClientCacheStatus, Disconnect, InventoryTransaction, ItemComponent, ItemStackRequest,
+        ItemStackResponse, LevelChunk, LevelEvent, Login, MobEffect, MoveActorAbsolute, MovePlayer,
+        PassengerJump, PlayStatus, RemoveActor, ResourcePackClientResponse, ResourcePackStack,
+        ResourcePacksInfo, S2CHandshake, SetActorData, SetTime, StartGame, SubChunk,
+        SubChunkRequest, SubClientLogin, TakeItemActor, Text, TickSync, UpdateAttributes,
+        UpdateBlock,
     },
     prelogin::{NetworkSettings, RequestNetworkSettings},
 };
 
+pub mod batch;
+pub mod codec;
+pub mod compression;
+pub mod encryption;
 pub mod header;
+pub mod phase;
 pub mod play;
 pub mod prelogin;
 
@@ -23,15 +34,21 @@ pub mod prelogin;
 macro_rules! build_packet {
     (
             $(
-                $packet:ident($struct:ident) = $id:expr
+                $packet:ident($struct:ident) = $id:expr => $direction:ident
             ),*
     ) => {
         /// Minecraft Packet represents a packet that may be sent over a Minecraft Connection.
         #[derive(Debug)]
         pub enum Packet {
             $(
-                $packet($struct)
-            ),*
+                $packet($struct),
+            )*
+            /// Unknown is a packet whose ID is not known to this library. The payload is kept
+            /// as raw bytes so that it can be forwarded as is.
+            Unknown {
+                header: PacketHeader,
+                payload: Vec<u8>,
+            },
         }
 
         /// PacketID represents the Minecraft Packet ID.
@@ -39,8 +56,42 @@ macro_rules! build_packet {
         #[repr(u32)]
         pub enum PacketID {
             $(
-                $packet = $id
-            ),*
+                $packet = $id,
+            )*
+            Unknown = u32::MAX,
+        }
+
+        impl PacketID {
+            /// Returns the Packet ID matching the ID read from a packet header, if it is known to
+            /// this library.
+            pub fn from_id(id: u32) -> Option<Self> {
+                match id {
+                    $(
+                        $id => Some(Self::$packet),
+                    )*
+                    _ => None,
+                }
+            }
+
+            /// Returns the name of the Minecraft Packet.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$packet => stringify!($packet),
+                    )*
+                    Self::Unknown => "Unknown",
+                }
+            }
+
+            /// Returns the direction in which the Minecraft Packet may be sent.
+            pub fn direction(&self) -> Direction {
+                match self {
+                    $(
+                        Self::$packet => Direction::$direction,
+                    )*
+                    Self::Unknown => Direction::Bidirectional,
+                }
+            }
         }
 
         impl Packet {
@@ -50,65 +101,318 @@ macro_rules! build_packet {
                     $(
                         Self::$packet { .. } => PacketID::$packet,
                     )*
+                    Self::Unknown { .. } => PacketID::Unknown,
                 }
             }
-        }
 
-        impl Binary for Packet {
-            fn serialize<W: Write>(&self, buf: &mut W) {
-                let header = PacketHeader::new(self.id() as u32);
-                header.serialize(buf);
+            /// Returns the ID of the Minecraft Packet as written in the packet header. Unlike
+            /// [`Packet::id`] this also returns the actual ID of unknown packets.
+            pub fn raw_id(&self) -> u32 {
+                match self {
+                    Self::Unknown { header, .. } => header.id(),
+                    packet => packet.id() as u32,
+                }
+            }
+
+            /// Returns true if the packet is not known to this library.
+            pub fn is_unknown(&self) -> bool {
+                matches!(self, Self::Unknown { .. })
+            }
+
+            /// Serializes the payload of the Minecraft Packet without the packet header, in the
+            /// latest protocol version.
+            pub fn serialize_payload<W: Write>(&self, buf: &mut W) {
+                self.serialize_payload_versioned(buf, PROTOCOL_VERSION);
+            }
 
+            /// Serializes the payload of the Minecraft Packet without the packet header, in the
+            /// provided protocol version.
+            pub fn serialize_payload_versioned<W: Write>(&self, buf: &mut W, protocol: u16) {
                 match self {
                     $(
-                        Self::$packet($struct) => $struct.serialize(buf),
+                        Self::$packet($struct) => $struct.serialize_versioned(buf, protocol),
                     )*
+                    Self::Unknown { payload, .. } => buf.write_all(payload).unwrap(),
                 }
             }
 
-            fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
-                let header = PacketHeader::deserialize(buf)?;
+            /// Deserializes the payload of the Minecraft Packet identified by the provided
+            /// packet header, in the latest protocol version.
+            pub fn deserialize_payload<R: Read + Buf>(
+                header: &PacketHeader,
+                buf: &mut R,
+            ) -> Result<Self> {
+                Self::deserialize_payload_versioned(header, buf, PROTOCOL_VERSION)
+            }
+
+            /// Deserializes the payload of the Minecraft Packet identified by the provided
+            /// packet header, in the provided protocol version. Packets that are not known to
+            /// this library are returned as [`Packet::Unknown`] with the remaining bytes of the
+            /// buffer as the payload.
+            pub fn deserialize_payload_versioned<R: Read + Buf>(
+                header: &PacketHeader,
+                buf: &mut R,
+                protocol: u16,
+            ) -> Result<Self> {
                 let id = header.id();
 
                 match id {
                     $(
                         $id => {
-                            let inner = $struct::deserialize(buf)?;
+                            let inner = $struct::deserialize_versioned(buf, protocol).map_err(|err| {
+                                Error::from_io(err).with_packet(stringify!($packet))
+                            })?;
                             Ok(Self::$packet(inner))
                         }
                     )*
-                    _ => Err(Error::new(ErrorKind::Other, format!("Packet ID {} not found!", id))),
+                    _ => {
+                        let mut payload = Vec::with_capacity(buf.remaining());
+                        buf.read_to_end(&mut payload)?;
+
+                        Ok(Self::Unknown {
+                            header: header.clone(),
+                            payload,
+                        })
+                    }
                 }
             }
+
+            /// Decodes a Minecraft Packet from the buffer, returning the typed error of this
+            /// library instead of an I/O error.
+            pub fn decode<R: Read + Buf>(buf: &mut R) -> $crate::error::Result<Self> {
+                Self::deserialize(buf).map_err(Error::from_io)
+            }
+        }
+
+        impl Binary for Packet {
+            fn serialize<W: Write>(&self, buf: &mut W) {
+                match self {
+                    Self::Unknown { header, .. } => header.serialize(buf),
+                    packet => PacketHeader::new(packet.raw_id()).serialize(buf),
+                }
+
+                self.serialize_payload(buf);
+            }
+
+            fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+                let header = PacketHeader::deserialize(buf)?;
+                Self::deserialize_payload(&header, buf)
+            }
         }
     }
 }
 
 build_packet!(
-    Login(Login) = 0x01,
-    PlayStatus(PlayStatus) = 0x02,
-    S2CHandshake(S2CHandshake) = 0x03,
-    C2SHandshake(C2SHandshake) = 0x04,
-    Disconnect(Disconnect) = 0x05,
-    ResourcePacksInfo(ResourcePacksInfo) = 0x06,
-    ResourcePackStack(ResourcePackStack) = 0x07,
-    ResourcePackClientResponse(ResourcePackClientResponse) = 0x08,
-    Text(Text) = 0x09,
-    SetTime(SetTime) = 0x0a,
-    StartGame(StartGame) = 0x0b,
-    AddPlayer(AddPlayer) = 0x0c,
-    TakeItemActor(TakeItemActor) = 0x11,
-    MoveActorAbsolute(MoveActorAbsolute) = 0x12,
-    MovePlayer(MovePlayer) = 0x13,
-    PassengerJump(PassengerJump) = 0x14,
-    UpdateBlock(UpdateBlock) = 0x15,
-    AddPainting(AddPainting) = 0x16,
-    TickSync(TickSync) = 0x17,
-    LevelEvent(LevelEvent) = 0x19,
-    BlockEvent(BlockEvent) = 0x1a,
-    ActorEvent(ActorEvent) = 0x1b,
-    MobEffect(MobEffect) = 0x1c,
-    UpdateAttributes(UpdateAttributes) = 0x1d,
-    RequestNetworkSettings(RequestNetworkSettings) = 0xc1,
-    NetworkSettings(NetworkSettings) = 0x8f
+    Login(Login) = 0x01 => Serverbound,
+    PlayStatus(PlayStatus) = 0x02 => Clientbound,
+    S2CHandshake(S2CHandshake) = 0x03 => Clientbound,
+    C2SHandshake(C2SHandshake) = 0x04 => Serverbound,
+    Disconnect(Disconnect) = 0x05 => Bidirectional,
+    ResourcePacksInfo(ResourcePacksInfo) = 0x06 => Clientbound,
+    ResourcePackStack(ResourcePackStack) = 0x07 => Clientbound,
+    ResourcePackClientResponse(ResourcePackClientResponse) = 0x08 => Serverbound,
+    Text(Text) = 0x09 => Bidirectional,
+    SetTime(SetTime) = 0x0a => Clientbound,
+    StartGame(StartGame) = 0x0b => Clientbound,
+    AddPlayer(AddPlayer) = 0x0c => Clientbound,
+    AddActor(AddActor) = 0x0d => Clientbound,
+    RemoveActor(RemoveActor) = 0x0e => Clientbound,
+    AddItemActor(AddItemActor) = 0x0f => Clientbound,
+    TakeItemActor(TakeItemActor) = 0x11 => Clientbound,
+    MoveActorAbsolute(MoveActorAbsolute) = 0x12 => Bidirectional,
+    MovePlayer(MovePlayer) = 0x13 => Bidirectional,
+    PassengerJump(PassengerJump) = 0x14 => Serverbound,
+    UpdateBlock(UpdateBlock) = 0x15 => Clientbound,
+    AddPainting(AddPainting) = 0x16 => Clientbound,
+    TickSync(TickSync) = 0x17 => Bidirectional,
+    LevelEvent(LevelEvent) = 0x19 => Bidirectional,
+    BlockEvent(BlockEvent) = 0x1a => Clientbound,
+    ActorEvent(ActorEvent) = 0x1b => Bidirectional,
+    MobEffect(MobEffect) = 0x1c => Clientbound,
+    UpdateAttributes(UpdateAttributes) = 0x1d => Clientbound,
+    InventoryTransaction(InventoryTransaction) = 0x1e => Bidirectional,
+    SetActorData(SetActorData) = 0x27 => Clientbound,
+    LevelChunk(LevelChunk) = 0x3a => Clientbound,
+    AvailableActorIdentifiers(AvailableActorIdentifiers) = 0x77 => Clientbound,
+    ClientCacheStatus(ClientCacheStatus) = 0x81 => Serverbound,
+    ClientCacheBlobStatus(ClientCacheBlobStatus) = 0x87 => Serverbound,
+    ClientCacheMissResponse(ClientCacheMissResponse) = 0x88 => Clientbound,
+    RequestNetworkSettings(RequestNetworkSettings) = 0xc1 => Serverbound,
+    SubClientLogin(SubClientLogin) = 0x5e => Serverbound,
+    NetworkSettings(NetworkSettings) = 0x8f => Clientbound,
+    ItemStackRequest(ItemStackRequest) = 0x93 => Serverbound,
+    ItemStackResponse(ItemStackResponse) = 0x94 => Clientbound,
+    ItemComponent(ItemComponent) = 0xa2 => Clientbound,
+    SubChunk(SubChunk) = 0xae => Clientbound,
+    SubChunkRequest(SubChunkRequest) = 0xaf => Serverbound
+);
+
+/// Direction is the direction in which a Minecraft Packet may be sent over a Minecraft Connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The packet is sent by the server to the client.
+    Clientbound,
+    /// The packet is sent by the client to the server.
+    Serverbound,
+    /// The packet may be sent by both the server and the client.
+    Bidirectional,
+}
+
+impl Direction {
+    /// Returns true if a packet of this direction may be received by the end of the connection
+    /// that expects packets of the provided direction.
+    pub fn allows(&self, expected: Direction) -> bool {
+        *self == Direction::Bidirectional || *self == expected
+    }
+}
+
+/// GamePacket is a Minecraft Packet along with the header it was received with or will be sent
+/// with. The header carries the sender and target sub client IDs which are used to route the
+/// packets of split screen players sharing a single connection.
+#[derive(Debug)]
+pub struct GamePacket {
+    pub header: PacketHeader,
+    pub packet: Packet,
+}
+
+impl GamePacket {
+    /// Creates and returns a new Game Packet exchanged with the primary client.
+    pub fn new(packet: Packet) -> Self {
+        let header = match &packet {
+            Packet::Unknown { header, .. } => header.clone(),
+            packet => PacketHeader::new(packet.raw_id()),
+        };
+
+        Self { header, packet }
+    }
+
+    /// Sets the ID of the sub client that sent the packet.
+    pub fn from_subclient(mut self, subclient_id: u8) -> Self {
+        self.header = self.header.with_sender_subclient_id(subclient_id);
+        self
+    }
+
+    /// Sets the ID of the sub client that the packet is meant for.
+    pub fn to_subclient(mut self, subclient_id: u8) -> Self {
+        self.header = self.header.with_target_subclient_id(subclient_id);
+        self
+    }
+
+    /// Returns the ID of the sub client that sent the packet.
+    pub fn sender_subclient_id(&self) -> u8 {
+        self.header.sender_subclient_id()
+    }
+
+    /// Returns the ID of the sub client that the packet is meant for.
+    pub fn target_subclient_id(&self) -> u8 {
+        self.header.target_subclient_id()
+    }
+}
+
+impl From<Packet> for GamePacket {
+    fn from(packet: Packet) -> Self {
+        Self::new(packet)
+    }
+}
+
+impl GamePacket {
+    /// Serializes the Game Packet in the provided protocol version.
+    pub fn write_versioned<W: Write>(&self, buf: &mut W, protocol: u16) {
+        self.header.serialize(buf);
+        self.packet.serialize_payload_versioned(buf, protocol);
+    }
+
+    /// Deserializes a Game Packet in the provided protocol version.
+    pub fn read_versioned<R: Read + Buf>(buf: &mut R, protocol: u16) -> Result<Self> {
+        let header = PacketHeader::deserialize(buf)?;
+        let packet = Packet::deserialize_payload_versioned(&header, buf, protocol)?;
+
+        Ok(Self { header, packet })
+    }
+}
+
+///
+/// Game Packets implementing Binary are encoded in the latest protocol version. Use
+/// [`GamePacket::write_versioned`] and [`GamePacket::read_versioned`] for the other versions.
+///
+impl Binary for GamePacket {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        self.write_versioned(buf, PROTOCOL_VERSION);
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        Self::read_versioned(buf, PROTOCOL_VERSION)
+    }
+}
+
+macro_rules! directional_packet {
+    ($name:ident, $direction:ident, $doc:literal) => {
+        #[doc = $doc]
+        ///
+        /// Decoding fails for packets known to be sent in the other direction only, while unknown
+        /// packets are passed through.
+        #[derive(Debug)]
+        pub struct $name(pub GamePacket);
+
+        impl $name {
+            /// Returns the Game Packet wrapped by the directional packet.
+            pub fn into_inner(self) -> GamePacket {
+                self.0
+            }
+
+            /// Deserializes a directional packet in the provided protocol version.
+            pub fn read_versioned<R: Read + Buf>(buf: &mut R, protocol: u16) -> Result<Self> {
+                let header = PacketHeader::deserialize(buf)?;
+                if let Some(id) = PacketID::from_id(header.id()) {
+                    if !id.direction().allows(Direction::$direction) {
+                        return Err(Error::UnexpectedDirection { packet: id.name() }.into());
+                    }
+                }
+
+                let packet = Packet::deserialize_payload_versioned(&header, buf, protocol)?;
+                Ok(Self(GamePacket { header, packet }))
+            }
+        }
+
+        impl TryFrom<GamePacket> for $name {
+            type Error = Error;
+
+            fn try_from(packet: GamePacket) -> std::result::Result<Self, Error> {
+                let id = packet.packet.id();
+                if !id.direction().allows(Direction::$direction) {
+                    return Err(Error::UnexpectedDirection { packet: id.name() });
+                }
+
+                Ok(Self(packet))
+            }
+        }
+
+        impl From<$name> for GamePacket {
+            fn from(packet: $name) -> Self {
+                packet.0
+            }
+        }
+
+        impl Binary for $name {
+            fn serialize<W: Write>(&self, buf: &mut W) {
+                self.0.serialize(buf);
+            }
+
+            fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+                Self::read_versioned(buf, PROTOCOL_VERSION)
+            }
+        }
+    };
+}
+
+directional_packet!(
+    ClientboundPacket,
+    Clientbound,
+    "ClientboundPacket is a Game Packet decoded by the client from the traffic of the server."
+);
+
+directional_packet!(
+    ServerboundPacket,
+    Serverbound,
+    "ServerboundPacket is a Game Packet decoded by the server from the traffic of the client."
 );
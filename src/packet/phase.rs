@@ -0,0 +1,154 @@
+use std::fmt;
+
+use crate::{data::generic::PlayStatusType, error::Error};
+
+use super::{Direction, Packet, PacketID};
+
+/// Phase is the phase of a Minecraft Connection. The phases follow each other in the order in
+/// which they are declared.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// The client requests the network settings and the server responds with them.
+    #[default]
+    NetworkSettings,
+    /// The client sends the Login packet and waits for the server to respond.
+    Login,
+    /// The server has started the encryption handshake and waits for the client to complete it.
+    Encryption,
+    /// The server sends the resource packs and the client responds with the packs it needs.
+    ResourcePacks,
+    /// The server has sent the StartGame packet and sends the world until the player spawns.
+    Spawning,
+    /// The player has spawned in the world.
+    InGame,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// PhaseTracker tracks the phase of a Minecraft Connection by consuming the packets sent in both
+/// directions, and reports the packets that are sent out of phase.
+#[derive(Debug, Default, Clone)]
+pub struct PhaseTracker {
+    phase: Phase,
+    login_sent: bool,
+    handshake_sent: bool,
+}
+
+impl PhaseTracker {
+    /// Creates and returns a new PhaseTracker for a connection that has just been opened.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current phase of the connection.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Consumes a packet sent in the provided direction, advancing the phase of the connection if
+    /// the packet completes it. Packets that are not expected in the current phase are reported as
+    /// [`Error::UnexpectedPacket`] and leave the phase unchanged. Disconnect and unknown packets are
+    /// accepted in every phase.
+    pub fn observe(
+        &mut self,
+        packet: &Packet,
+        direction: Direction,
+    ) -> crate::error::Result<Phase> {
+        let id = packet.id();
+
+        if matches!(id, PacketID::Disconnect | PacketID::Unknown) {
+            return Ok(self.phase);
+        }
+
+        if !id.direction().allows(direction) {
+            return Err(Error::UnexpectedDirection { packet: id.name() });
+        }
+
+        // A failed PlayStatus may be sent in any phase before the player spawns, right before the
+        // server closes the connection.
+        if let Packet::PlayStatus(status) = packet {
+            if self.phase < Phase::InGame && !is_success(&status.status) {
+                return Ok(self.phase);
+            }
+        }
+
+        let next = match (self.phase, packet) {
+            (Phase::NetworkSettings, Packet::RequestNetworkSettings(_)) => Some(self.phase),
+            (Phase::NetworkSettings, Packet::NetworkSettings(_)) => Some(Phase::Login),
+
+            (Phase::Login, Packet::Login(_)) if !self.login_sent => {
+                self.login_sent = true;
+                Some(self.phase)
+            }
+            (Phase::Login, Packet::S2CHandshake(_)) if self.login_sent => Some(Phase::Encryption),
+            // Servers in offline mode skip the encryption and succeed the login right away.
+            (Phase::Login, Packet::PlayStatus(_)) if self.login_sent => Some(Phase::ResourcePacks),
+
+            (Phase::Encryption, Packet::C2SHandshake(_)) if !self.handshake_sent => {
+                self.handshake_sent = true;
+                Some(self.phase)
+            }
+            (Phase::Encryption, Packet::PlayStatus(_)) if self.handshake_sent => {
+                Some(Phase::ResourcePacks)
+            }
+
+            (Phase::ResourcePacks, Packet::ResourcePacksInfo(_))
+            | (Phase::ResourcePacks, Packet::ResourcePackStack(_))
+            | (Phase::ResourcePacks, Packet::ResourcePackClientResponse(_)) => Some(self.phase),
+            (Phase::ResourcePacks, Packet::StartGame(_)) => Some(Phase::Spawning),
+
+            (Phase::Spawning, Packet::PlayStatus(status))
+                if matches!(status.status, PlayStatusType::PlayerSpawn) =>
+            {
+                Some(Phase::InGame)
+            }
+            // Servers may send any PlayStatus once the player has spawned, such as a failure right
+            // before they close the connection.
+            (Phase::InGame, Packet::PlayStatus(_)) => Some(self.phase),
+            (Phase::Spawning, packet) | (Phase::InGame, packet) if is_game_packet(packet) => {
+                Some(self.phase)
+            }
+
+            _ => None,
+        };
+
+        match next {
+            Some(phase) => {
+                self.phase = phase;
+                Ok(phase)
+            }
+            None => Err(Error::UnexpectedPacket {
+                packet: id.name(),
+                phase: self.phase,
+            }),
+        }
+    }
+}
+
+fn is_success(status: &PlayStatusType) -> bool {
+    matches!(
+        status,
+        PlayStatusType::Success | PlayStatusType::PlayerSpawn
+    )
+}
+
+/// Returns true if the packet may only be sent once the StartGame packet has been sent.
+fn is_game_packet(packet: &Packet) -> bool {
+    !matches!(
+        packet,
+        Packet::RequestNetworkSettings(_)
+            | Packet::NetworkSettings(_)
+            | Packet::Login(_)
+            | Packet::S2CHandshake(_)
+            | Packet::C2SHandshake(_)
+            | Packet::ResourcePacksInfo(_)
+            | Packet::ResourcePackStack(_)
+            | Packet::ResourcePackClientResponse(_)
+            | Packet::StartGame(_)
+            | Packet::PlayStatus(_)
+    )
+}
@@ -2,7 +2,7 @@ use std::io::{Read, Result, Write};
 
 use binary::{
     binary::Binary,
-    datatypes::{Bool, VarI32, VarI64, VarU32, VarU64, F32, I32, I64, U64, U8},
+    datatypes::{Bool, VarI32, VarI64, VarU32, VarU64, F32, I32, I64, U16, U32, U64, U8},
 };
 use binary_derive::Binary;
 use byteorder::{BE, LE};
@@ -10,30 +10,47 @@ use bytes::Buf;
 use commons::is_variant;
 use nbt::{encoding::NetworkLittleEndian, NBTCompound};
 
-use crate::data::{
-    block::BlockEntry,
-    education::EducationSharedResourceURI,
-    entity::attribute::Attribute,
-    entity::mob::{MobEffectOperation, MobEffectType},
-    entity::teleport::{MovementMode, TeleportCause},
-    event::{ActorEventType, LevelEventType},
-    game::{
-        ChatRestriction, EditorWorldType, ExperimentData, GamePublishSetting, GameRule, GameType,
-        PlayerPermission, SpawnBiomeType,
+use crate::{
+    cache::BlobCache,
+    data::{
+        block::BlockEntry,
+        check_len,
+        chunk::{
+            CacheBlob, ChunkData, SubChunkEntry, SubChunkOffset, SubChunkPos, SubChunkRequestMode,
+        },
+        education::EducationSharedResourceURI,
+        entity::ability::AbilityData,
+        entity::attribute::{Attribute, AttributeValue},
+        entity::link::EntityLink,
+        entity::metadata::EntityMetadata,
+        entity::mob::{MobEffectOperation, MobEffectType},
+        entity::property::EntityProperties,
+        entity::teleport::{MovementMode, TeleportCause},
+        event::{ActorEventType, LevelEventType},
+        game::{
+            ChatRestriction, EditorWorldType, ExperimentData, GamePublishSetting, GameRule,
+            GameType, PlayerPermission, SpawnBiomeType,
+        },
+        generic::{InteractAction, PlayStatusType, TextType},
+        inventory::{InventoryAction, InventoryTransactionData, LegacyItemSlot},
+        item::{ItemComponentEntry, ItemEntry, ItemInstance},
+        login::ConnectionRequest,
+        player::PlayerMoveSettings,
+        resource_pack::{
+            BehaviourPackInfo, PackURL, ResourcePackResponse, StackResourcePack, TexturePackInfo,
+        },
+        stack_request::{StackRequest, StackResponse},
+        List, ListU32, Position, Rotation, Slice, SliceU16, SliceU32, UBlockPos, VarString,
+        Velocity, UUID,
     },
-    generic::{InteractAction, PlayStatusType, TextType},
-    item::{ItemEntry, ItemInstance},
-    player::PlayerMoveSettings,
-    resource_pack::{
-        BehaviourPackInfo, PackURL, ResourcePackResponse, StackResourcePack, TexturePackInfo,
-    },
-    ByteSlice, Position, Rotation, Slice, SliceU16, SliceU32, UBlockPos, VarString, Velocity, UUID,
+    error::Error,
+    versioned_struct,
 };
 
 #[derive(Default, Debug, Binary)]
 pub struct Login {
     pub protocol: I32<BE>,
-    pub data: ByteSlice,
+    pub connection_request: ConnectionRequest,
 }
 
 #[derive(Default, Debug, Binary)]
@@ -43,7 +60,7 @@ pub struct PlayStatus {
 
 #[derive(Default, Debug, Binary)]
 pub struct S2CHandshake {
-    pub jwt: ByteSlice,
+    pub jwt: VarString,
 }
 
 #[derive(Default, Debug, Binary)]
@@ -73,7 +90,9 @@ impl Binary for Disconnect {
         let mut message = None;
 
         if !hide_screen.0 {
-            message = Some(VarString::deserialize(buf)?);
+            let msg = VarString::deserialize(buf)
+                .map_err(|err| Error::from_io(err).with_field("message"))?;
+            message = Some(msg);
         }
 
         Ok(Self {
@@ -194,82 +213,84 @@ pub struct SetTime {
     pub time: VarI32,
 }
 
-#[derive(Default, Debug, Binary)]
-pub struct StartGame {
-    pub entity_unique_id: VarI64,
-    pub entity_runtime_id: VarU64,
-    pub game_type: GameType,
-    pub position: Position,
-    pub pitch: F32<LE>,
-    pub yaw: F32<LE>,
-    pub world_seed: I64<LE>,
-    pub spawn_biome_type: SpawnBiomeType,
-    pub user_defined_biome_name: VarString,
-    pub dimension: VarI32,
-    pub generator: VarI32,
-    pub world_game_type: GameType,
-    pub difficulty: VarI32,
-    pub world_spawn: UBlockPos,
-    pub achievements_disabled: Bool,
-    pub editor_world_type: EditorWorldType,
-    pub created_in_editor: Bool,
-    pub exported_from_editor: Bool,
-    pub day_cycle_lock_time: VarI32,
-    pub education_edition_offer: VarI32,
-    pub education_features_enabled: Bool,
-    pub education_product_id: VarString,
-    pub rain_level: F32<LE>,
-    pub lightning_level: F32<LE>,
-    pub confirmed_platform_locked_content: Bool,
-    pub multiplayer_game: Bool,
-    pub lan_broadcast_enabled: Bool,
-    pub xbl_broadcast_mode: GamePublishSetting,
-    pub platform_broadcast_mode: GamePublishSetting,
-    pub commands_enabled: Bool,
-    pub texture_pack_required: Bool,
-    pub game_rules: Slice<GameRule>,
-    pub experiments: SliceU32<ExperimentData>,
-    pub experiments_previously_toggled: Bool,
-    pub bonus_chest_enabled: Bool,
-    pub start_with_map_enabled: Bool,
-    pub player_permission: PlayerPermission,
-    pub server_chunk_tick_radius: I32<LE>,
-    pub has_locked_behaviour_pack: Bool,
-    pub has_locked_texture_pack: Bool,
-    pub from_locked_world_template: Bool,
-    pub msa_gamertags_only: Bool,
-    pub from_world_template: Bool,
-    pub world_template_settings_locked: Bool,
-    pub only_spawn_v1_villagers: Bool,
-    pub persona_disabled: Bool,
-    pub custom_skins_disabled: Bool,
-    pub emote_chat_muted: Bool,
-    pub base_game_version: VarString,
-    pub limited_world_width: I32<LE>,
-    pub limited_world_depth: I32<LE>,
-    pub new_nether: Bool,
-    pub education_shared_uri: EducationSharedResourceURI,
-    pub force_experimental_gameplay: Bool,
-    pub chat_restriction_level: ChatRestriction,
-    pub disable_player_interactions: Bool,
-    pub level_id: VarString,
-    pub world_name: VarString,
-    pub template_content_identity: VarString,
-    pub trial: Bool,
-    pub player_move_settings: PlayerMoveSettings,
-    pub time: I64<LE>,
-    pub enchantment_seed: VarI32,
-    pub blocks: Slice<BlockEntry>,
-    pub items: Slice<ItemEntry>,
-    pub multiplayer_correlation_id: VarString,
-    pub server_authoritative_inventory: Bool,
-    pub game_version: VarString,
-    pub property_data: NBTCompound<NetworkLittleEndian>,
-    pub server_blockstate_checksum: U64<LE>,
-    pub world_template_id: UUID,
-    pub clientside_generation: Bool,
-    pub use_block_network_id_hashes: Bool,
-    pub server_authoritative_sound: Bool,
+versioned_struct! {
+    #[derive(Default, Debug)]
+    pub struct StartGame {
+        pub entity_unique_id: VarI64,
+        pub entity_runtime_id: VarU64,
+        pub game_type: GameType,
+        pub position: Position,
+        pub pitch: F32<LE>,
+        pub yaw: F32<LE>,
+        pub world_seed: I64<LE>,
+        pub spawn_biome_type: SpawnBiomeType,
+        pub user_defined_biome_name: VarString,
+        pub dimension: VarI32,
+        pub generator: VarI32,
+        pub world_game_type: GameType,
+        pub difficulty: VarI32,
+        pub world_spawn: UBlockPos,
+        pub achievements_disabled: Bool,
+        pub editor_world_type: EditorWorldType,
+        pub created_in_editor: Bool,
+        pub exported_from_editor: Bool,
+        pub day_cycle_lock_time: VarI32,
+        pub education_edition_offer: VarI32,
+        pub education_features_enabled: Bool,
+        pub education_product_id: VarString,
+        pub rain_level: F32<LE>,
+        pub lightning_level: F32<LE>,
+        pub confirmed_platform_locked_content: Bool,
+        pub multiplayer_game: Bool,
+        pub lan_broadcast_enabled: Bool,
+        pub xbl_broadcast_mode: GamePublishSetting,
+        pub platform_broadcast_mode: GamePublishSetting,
+        pub commands_enabled: Bool,
+        pub texture_pack_required: Bool,
+        pub game_rules: Slice<GameRule>,
+        pub experiments: SliceU32<ExperimentData>,
+        pub experiments_previously_toggled: Bool,
+        pub bonus_chest_enabled: Bool,
+        pub start_with_map_enabled: Bool,
+        pub player_permission: PlayerPermission,
+        pub server_chunk_tick_radius: I32<LE>,
+        pub has_locked_behaviour_pack: Bool,
+        pub has_locked_texture_pack: Bool,
+        pub from_locked_world_template: Bool,
+        pub msa_gamertags_only: Bool,
+        pub from_world_template: Bool,
+        pub world_template_settings_locked: Bool,
+        pub only_spawn_v1_villagers: Bool,
+        pub persona_disabled: Bool,
+        pub custom_skins_disabled: Bool,
+        pub emote_chat_muted: Bool,
+        pub base_game_version: VarString,
+        pub limited_world_width: I32<LE>,
+        pub limited_world_depth: I32<LE>,
+        pub new_nether: Bool,
+        pub education_shared_uri: EducationSharedResourceURI,
+        pub force_experimental_gameplay: Bool,
+        pub chat_restriction_level: ChatRestriction,
+        pub disable_player_interactions: Bool,
+        pub level_id: VarString,
+        pub world_name: VarString,
+        pub template_content_identity: VarString,
+        pub trial: Bool,
+        pub player_move_settings: PlayerMoveSettings,
+        pub time: I64<LE>,
+        pub enchantment_seed: VarI32,
+        pub blocks: Slice<BlockEntry>,
+        pub items: Slice<ItemEntry>,
+        pub multiplayer_correlation_id: VarString,
+        pub server_authoritative_inventory: Bool,
+        pub game_version: VarString,
+        pub property_data: NBTCompound<NetworkLittleEndian>,
+        pub server_blockstate_checksum: U64<LE>,
+        pub world_template_id: UUID,
+        pub clientside_generation: Bool,
+        pub use_block_network_id_hashes: Bool,
+        pub server_authoritative_sound: Bool,
+    }
 }
 
 #[derive(Default, Debug, Binary)]
@@ -285,10 +306,48 @@ pub struct AddPlayer {
     pub head_yaw: F32<LE>,
     pub held_item: ItemInstance,
     pub game_type: GameType,
+    pub metadata: EntityMetadata,
+    pub properties: EntityProperties,
+    pub ability_data: AbilityData,
+    pub links: Slice<EntityLink>,
     pub device_id: VarString,
     pub build_platform: I32<LE>,
 }
 
+#[derive(Default, Debug, Binary)]
+pub struct AddActor {
+    pub entity_unique_id: VarI64,
+    pub entity_runtime_id: VarU64,
+    /// EntityType is the identifier of the type of the entity, such as `minecraft:zombie`.
+    pub entity_type: VarString,
+    pub position: Position,
+    pub velocity: Velocity,
+    pub pitch: F32<LE>,
+    pub yaw: F32<LE>,
+    pub head_yaw: F32<LE>,
+    pub body_yaw: F32<LE>,
+    pub attributes: Slice<AttributeValue>,
+    pub metadata: EntityMetadata,
+    pub properties: EntityProperties,
+    pub links: Slice<EntityLink>,
+}
+
+#[derive(Default, Debug, Binary)]
+pub struct RemoveActor {
+    pub entity_unique_id: VarI64,
+}
+
+#[derive(Default, Debug, Binary)]
+pub struct AddItemActor {
+    pub entity_unique_id: VarI64,
+    pub entity_runtime_id: VarU64,
+    pub item: ItemInstance,
+    pub position: Position,
+    pub velocity: Velocity,
+    pub metadata: EntityMetadata,
+    pub from_fishing: Bool,
+}
+
 #[derive(Default, Debug, Binary)]
 pub struct TakeItemActor {
     pub item_runtime_id: VarU64,
@@ -377,6 +436,292 @@ pub struct MobEffect {
     pub duration: VarI32,
 }
 
+#[derive(Default, Debug)]
+pub struct InventoryTransaction {
+    pub legacy_request_id: VarI32,
+    pub legacy_set_item_slots: Slice<LegacyItemSlot>,
+    pub actions: Slice<InventoryAction>,
+    pub transaction_data: InventoryTransactionData,
+}
+
+///
+/// Special Serialization and Deserialization of InventoryTransaction Packet exists because the
+/// legacy slots are only encoded if the legacy request ID is not zero, and the type of the
+/// transaction data is encoded before the actions while the data itself comes after them.
+///
+impl Binary for InventoryTransaction {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        self.legacy_request_id.serialize(buf);
+
+        if self.legacy_request_id.0 != 0 {
+            self.legacy_set_item_slots.serialize(buf);
+        }
+
+        VarU32::new(self.transaction_data.transaction_type()).serialize(buf);
+        self.actions.serialize(buf);
+        self.transaction_data.serialize_body(buf);
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let legacy_request_id = VarI32::deserialize(buf)?;
+        let mut legacy_set_item_slots = Default::default();
+
+        if legacy_request_id.0 != 0 {
+            legacy_set_item_slots = Slice::deserialize(buf)
+                .map_err(|err| Error::from_io(err).with_field("legacy_set_item_slots"))?;
+        }
+
+        let transaction_type = VarU32::deserialize(buf)?.0;
+        let actions =
+            Slice::deserialize(buf).map_err(|err| Error::from_io(err).with_field("actions"))?;
+        let transaction_data = InventoryTransactionData::deserialize_body(transaction_type, buf)
+            .map_err(|err| Error::from_io(err).with_field("transaction_data"))?;
+
+        Ok(Self {
+            legacy_request_id,
+            legacy_set_item_slots,
+            actions,
+            transaction_data,
+        })
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct LevelChunk {
+    pub chunk_x: VarI32,
+    pub chunk_z: VarI32,
+    pub sub_chunk_request_mode: SubChunkRequestMode,
+    /// BlobHashes are the hashes of the sub chunks and the biomes of the chunk, sent if the client
+    /// has enabled the blob cache. The payload then only holds the border blocks and the block
+    /// entities.
+    pub blob_hashes: Option<Vec<u64>>,
+    pub raw_payload: Vec<u8>,
+}
+
+impl LevelChunk {
+    /// Creates and returns a new LevelChunk holding the chunk data, without using the blob cache.
+    pub fn new(chunk_x: i32, chunk_z: i32, data: &ChunkData) -> crate::error::Result<Self> {
+        Ok(Self {
+            chunk_x: VarI32::new(chunk_x),
+            chunk_z: VarI32::new(chunk_z),
+            sub_chunk_request_mode: SubChunkRequestMode::Legacy(data.sub_chunks.len() as u32),
+            blob_hashes: None,
+            raw_payload: data.encode()?,
+        })
+    }
+
+    /// Creates and returns a new LevelChunk for a client that has enabled the blob cache. The sub
+    /// chunks and the biomes are added to the cache as blobs, and only their hashes are sent.
+    pub fn cached(
+        chunk_x: i32,
+        chunk_z: i32,
+        data: &ChunkData,
+        cache: &mut BlobCache,
+    ) -> crate::error::Result<Self> {
+        let (blobs, raw_payload) = data.encode_cached()?;
+
+        Ok(Self {
+            chunk_x: VarI32::new(chunk_x),
+            chunk_z: VarI32::new(chunk_z),
+            sub_chunk_request_mode: SubChunkRequestMode::Legacy(data.sub_chunks.len() as u32),
+            blob_hashes: Some(blobs.into_iter().map(|blob| cache.insert(blob)).collect()),
+            raw_payload,
+        })
+    }
+
+    /// Decodes the chunk data of the payload. This is only possible for the legacy request mode
+    /// without the blob cache, as the payload does not hold the sub chunks otherwise.
+    pub fn chunk_data(
+        &self,
+        biome_sections: usize,
+        lowest_index: i8,
+    ) -> crate::error::Result<ChunkData> {
+        match (self.sub_chunk_request_mode, &self.blob_hashes) {
+            (SubChunkRequestMode::Legacy(count), None) => ChunkData::decode(
+                &self.raw_payload,
+                count as usize,
+                biome_sections,
+                lowest_index,
+            ),
+            _ => Err(Error::InvalidChunk(
+                "the payload does not hold the sub chunks".into(),
+            )),
+        }
+    }
+}
+
+///
+/// Special Serialization and Deserialization of LevelChunk Packet exists because the sub chunk
+/// count doubles as the request mode, and the blob hashes are only encoded if the cache is enabled.
+///
+impl Binary for LevelChunk {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        self.chunk_x.serialize(buf);
+        self.chunk_z.serialize(buf);
+
+        VarU32::new(self.sub_chunk_request_mode.sub_chunk_count()).serialize(buf);
+        if let SubChunkRequestMode::Limited { highest_sub_chunk } = self.sub_chunk_request_mode {
+            U16::<LE>::new(highest_sub_chunk).serialize(buf);
+        }
+
+        Bool::new(self.blob_hashes.is_some()).serialize(buf);
+        if let Some(hashes) = &self.blob_hashes {
+            VarU32::new(hashes.len() as u32).serialize(buf);
+            for hash in hashes {
+                U64::<LE>::new(*hash).serialize(buf);
+            }
+        }
+
+        VarU32::new(self.raw_payload.len() as u32).serialize(buf);
+        buf.write_all(&self.raw_payload).unwrap();
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let chunk_x = VarI32::deserialize(buf)?;
+        let chunk_z = VarI32::deserialize(buf)?;
+
+        let sub_chunk_request_mode = match VarU32::deserialize(buf)?.0 {
+            u32::MAX => SubChunkRequestMode::Limitless,
+            count if count == u32::MAX - 1 => SubChunkRequestMode::Limited {
+                highest_sub_chunk: U16::<LE>::deserialize(buf)?.0,
+            },
+            count => SubChunkRequestMode::Legacy(count),
+        };
+
+        let mut blob_hashes = None;
+        if Bool::deserialize(buf)?.0 {
+            let len = VarU32::deserialize(buf)?.0 as usize;
+            check_len(len, 8, buf.remaining(), "blob_hashes")?;
+
+            let mut hashes = Vec::with_capacity(len);
+            for _ in 0..len {
+                hashes.push(U64::<LE>::deserialize(buf)?.0);
+            }
+            blob_hashes = Some(hashes);
+        }
+
+        let len = VarU32::deserialize(buf)?.0 as usize;
+        if len > buf.remaining() {
+            let err = Error::OversizePrefix {
+                len,
+                max: buf.remaining(),
+            };
+            return Err(err.with_field("raw_payload").into());
+        }
+
+        let mut raw_payload = vec![0u8; len];
+        buf.read_exact(&mut raw_payload)?;
+
+        Ok(Self {
+            chunk_x,
+            chunk_z,
+            sub_chunk_request_mode,
+            blob_hashes,
+            raw_payload,
+        })
+    }
+}
+
+/// SubChunk is sent by the server in response to a SubChunkRequest, holding an entry for each of
+/// the requested sub chunks.
+#[derive(Default, Debug)]
+pub struct SubChunk {
+    /// CacheEnabled is true if the client has enabled the blob cache, in which case each entry
+    /// carries the hash of its sub chunk.
+    pub cache_enabled: bool,
+    pub dimension: VarI32,
+    pub position: SubChunkPos,
+    pub entries: Vec<SubChunkEntry>,
+}
+
+impl SubChunk {
+    /// Creates and returns a new SubChunk answering a SubChunkRequest with the entries, without
+    /// using the blob cache.
+    pub fn respond(request: &SubChunkRequest, entries: Vec<SubChunkEntry>) -> Self {
+        Self {
+            cache_enabled: false,
+            dimension: request.dimension.clone(),
+            position: request.position.clone(),
+            entries,
+        }
+    }
+}
+
+///
+/// Special Serialization and Deserialization of SubChunk Packet exists because the encoding of the
+/// entries depends on whether the blob cache is enabled.
+///
+impl Binary for SubChunk {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        Bool::new(self.cache_enabled).serialize(buf);
+        self.dimension.serialize(buf);
+        self.position.serialize(buf);
+
+        U32::<LE>::new(self.entries.len() as u32).serialize(buf);
+        for entry in &self.entries {
+            entry.write(buf, self.cache_enabled);
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let cache_enabled = Bool::deserialize(buf)?.0;
+        let dimension = VarI32::deserialize(buf)?;
+        let position = SubChunkPos::deserialize(buf)?;
+
+        // Every entry is at least 5 bytes long, which bounds the number of entries.
+        let len = U32::<LE>::deserialize(buf)?.0 as usize;
+        check_len(len, 5, buf.remaining(), "entries")?;
+
+        let mut entries = Vec::with_capacity(len);
+        for _ in 0..len {
+            let entry =
+                SubChunkEntry::read(buf, cache_enabled).map_err(|err| err.with_field("entries"))?;
+            entries.push(entry);
+        }
+
+        Ok(Self {
+            cache_enabled,
+            dimension,
+            position,
+            entries,
+        })
+    }
+}
+
+/// SubChunkRequest is sent by the client to request the sub chunks at the offsets from the
+/// position, when the LevelChunk packet used a limited or limitless sub chunk request mode.
+#[derive(Default, Debug, Binary)]
+pub struct SubChunkRequest {
+    pub dimension: VarI32,
+    pub position: SubChunkPos,
+    pub offsets: ListU32<SubChunkOffset, 3>,
+}
+
+#[derive(Default, Debug, Binary)]
+pub struct ItemStackRequest {
+    pub requests: List<StackRequest, 7>,
+}
+
+#[derive(Default, Debug, Binary)]
+pub struct ItemStackResponse {
+    pub responses: List<StackResponse, 2>,
+}
+
+#[derive(Default, Debug, Binary)]
+pub struct SetActorData {
+    pub entity_runtime_id: VarU64,
+    pub metadata: EntityMetadata,
+    pub properties: EntityProperties,
+    pub tick: VarU64,
+}
+
+/// AvailableActorIdentifiers sends the identifiers of all the entity types available to the
+/// client, including the ones added by behaviour packs.
+#[derive(Default, Debug, Binary)]
+pub struct AvailableActorIdentifiers {
+    pub identifiers: NBTCompound<NetworkLittleEndian>,
+}
+
 #[derive(Default, Debug, Binary)]
 pub struct UpdateAttributes {
     pub entity_runtime_id: VarU64,
@@ -419,3 +764,75 @@ pub struct HurtArmor {
     pub cause: VarI32,
     pub damage: VarI32,
 }
+
+#[derive(Default, Debug, Binary)]
+pub struct SubClientLogin {
+    pub connection_request: ConnectionRequest,
+}
+
+/// ClientCacheStatus is sent by the client after the login to tell the server whether it supports
+/// the blob cache.
+#[derive(Default, Debug, Binary)]
+pub struct ClientCacheStatus {
+    pub enabled: Bool,
+}
+
+/// ClientCacheBlobStatus is sent by the client to report which of the blobs it was sent the hashes
+/// of are missing from its cache and which it already has.
+#[derive(Default, Debug)]
+pub struct ClientCacheBlobStatus {
+    pub miss_hashes: Vec<u64>,
+    pub hit_hashes: Vec<u64>,
+}
+
+///
+/// Special Serialization and Deserialization of ClientCacheBlobStatus Packet exists because the
+/// lengths of both lists of hashes are encoded before the hashes.
+///
+impl Binary for ClientCacheBlobStatus {
+    fn serialize<W: Write>(&self, buf: &mut W) {
+        VarU32::new(self.miss_hashes.len() as u32).serialize(buf);
+        VarU32::new(self.hit_hashes.len() as u32).serialize(buf);
+
+        for hash in self.miss_hashes.iter().chain(&self.hit_hashes) {
+            U64::<LE>::new(*hash).serialize(buf);
+        }
+    }
+
+    fn deserialize<R: Read + Buf>(buf: &mut R) -> Result<Self> {
+        let misses = VarU32::deserialize(buf)?.0 as usize;
+        let hits = VarU32::deserialize(buf)?.0 as usize;
+
+        let len = misses.saturating_add(hits);
+        check_len(len, 8, buf.remaining(), "hashes")?;
+
+        let mut miss_hashes = Vec::with_capacity(misses);
+        for _ in 0..misses {
+            miss_hashes.push(U64::<LE>::deserialize(buf)?.0);
+        }
+
+        let mut hit_hashes = Vec::with_capacity(hits);
+        for _ in 0..hits {
+            hit_hashes.push(U64::<LE>::deserialize(buf)?.0);
+        }
+
+        Ok(Self {
+            miss_hashes,
+            hit_hashes,
+        })
+    }
+}
+
+/// ClientCacheMissResponse is sent by the server in response to a ClientCacheBlobStatus, holding
+/// the blobs the client reported missing. Every blob is at least 9 bytes long.
+#[derive(Default, Debug, Binary)]
+pub struct ClientCacheMissResponse {
+    pub blobs: List<CacheBlob, 9>,
+}
+
+/// ItemComponent is sent by the server during the login to define the custom items, along with
+/// their components. Every item is at least 3 bytes long.
+#[derive(Default, Debug, Binary)]
+pub struct ItemComponent {
+    pub items: List<ItemComponentEntry, 3>,
+}
@@ -2,6 +2,8 @@ use binary::datatypes::{Bool, F32, I32, U16, U8};
 use binary_derive::Binary;
 use byteorder::{BE, LE};
 
+use super::compression::CompressionAlgorithm;
+
 /// RequestNetworkSettings is sent by the client to request network settings, such as compression,
 /// from the server.
 #[derive(Default, Debug, Binary)]
@@ -20,7 +22,7 @@ pub struct NetworkSettings {
     pub compression_threshold: U16<LE>,
 
     /// CompressionAlgorithm is the algorithm that is used to compress packets.
-    pub compression_alogrithm: U16<LE>,
+    pub compression_algorithm: CompressionAlgorithm,
 
     /// This regulates whether the client should throttle players when exceeding of the threshold. Players
     /// outside threshold will not be ticked, improving performance on low-end devices.
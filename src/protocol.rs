@@ -0,0 +1,131 @@
+use std::io::{Read, Result, Write};
+
+use binary::binary::Binary;
+use bytes::Buf;
+
+use crate::{error::Error, packet::prelogin::RequestNetworkSettings};
+
+/// This is the protocol version of Minecraft 1.20.30.
+pub const PROTOCOL_1_20_30: u16 = 618;
+
+/// These are the protocol versions supported by this library along with their Minecraft versions,
+/// from the oldest to the latest. A version is only listed once every packet has been checked
+/// against its layout, and the fields it adds or removes are gated using [`versioned_struct`].
+/// Only the latest version has been checked so far, so no field is gated yet.
+pub const SUPPORTED_PROTOCOLS: &[(u16, &str)] = &[(PROTOCOL_1_20_30, "1.20.30")];
+
+/// Returns true if the protocol version is supported by this library.
+pub fn is_supported(protocol: u16) -> bool {
+    SUPPORTED_PROTOCOLS.iter().any(|(p, _)| *p == protocol)
+}
+
+/// Returns the Minecraft version of the protocol version, if it is supported by this library.
+pub fn minecraft_version(protocol: u16) -> Option<&'static str> {
+    SUPPORTED_PROTOCOLS
+        .iter()
+        .find(|(p, _)| *p == protocol)
+        .map(|(_, version)| *version)
+}
+
+/// Returns the protocol version negotiated by the RequestNetworkSettings packet of the client,
+/// failing if the protocol version is not supported by this library.
+pub fn negotiate(request: &RequestNetworkSettings) -> crate::error::Result<u16> {
+    let protocol = request.client_protocol.0;
+
+    match u16::try_from(protocol) {
+        Ok(protocol) if is_supported(protocol) => Ok(protocol),
+        _ => Err(Error::UnsupportedProtocol(protocol)),
+    }
+}
+
+/// VersionedBinary is implemented by the types whose encoding depends on the protocol version
+/// negotiated with the client. Every type implementing [`Binary`] has the same encoding in all
+/// protocol versions.
+pub trait VersionedBinary: Sized {
+    fn serialize_versioned<W: Write>(&self, buf: &mut W, protocol: u16);
+
+    fn deserialize_versioned<R: Read + Buf>(buf: &mut R, protocol: u16) -> Result<Self>;
+}
+
+impl<T: Binary> VersionedBinary for T {
+    fn serialize_versioned<W: Write>(&self, buf: &mut W, _protocol: u16) {
+        self.serialize(buf);
+    }
+
+    fn deserialize_versioned<R: Read + Buf>(buf: &mut R, _protocol: u16) -> Result<Self> {
+        T::deserialize(buf)
+    }
+}
+
+/// Declares a structure whose fields may be gated on the protocol version and implements
+/// [`VersionedBinary`] for it. A field followed by `=> since V` is only encoded in protocol
+/// versions V and later, while a field followed by `=> until V` is only encoded in protocol
+/// versions before V. Gated fields hold their default value when they are not encoded.
+#[macro_export]
+macro_rules! versioned_struct {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                pub $field:ident: $ty:ty $(=> $gate:ident $version:expr)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        pub struct $name {
+            $(
+                $(#[$field_meta])*
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::protocol::VersionedBinary for $name {
+            fn serialize_versioned<W: std::io::Write>(&self, buf: &mut W, protocol: u16) {
+                $(
+                    if $crate::versioned_struct!(@included protocol $(, $gate $version)?) {
+                        $crate::protocol::VersionedBinary::serialize_versioned(
+                            &self.$field,
+                            buf,
+                            protocol,
+                        );
+                    }
+                )*
+            }
+
+            fn deserialize_versioned<R: std::io::Read + bytes::Buf>(
+                buf: &mut R,
+                protocol: u16,
+            ) -> std::io::Result<Self> {
+                $(
+                    let $field = if $crate::versioned_struct!(@included protocol $(, $gate $version)?) {
+                        <$ty as $crate::protocol::VersionedBinary>::deserialize_versioned(
+                            buf,
+                            protocol,
+                        )
+                        .map_err(|err| {
+                            $crate::error::Error::from_io(err).with_field(stringify!($field))
+                        })?
+                    } else {
+                        Default::default()
+                    };
+                )*
+
+                Ok(Self {
+                    $(
+                        $field,
+                    )*
+                })
+            }
+        }
+    };
+    (@included $protocol:ident) => {
+        true
+    };
+    (@included $protocol:ident, since $version:expr) => {
+        $protocol >= $version
+    };
+    (@included $protocol:ident, until $version:expr) => {
+        $protocol < $version
+    };
+}
@@ -0,0 +1,146 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE, LE};
+
+use crate::error::Result;
+
+use super::{
+    frame::{FLAG_ACK, FLAG_NACK, FLAG_VALID},
+    serial,
+};
+
+/// This is the maximum number of records decoded from a single acknowledgement, which bounds the
+/// work done for a malicious datagram.
+const MAX_RECORDS: u16 = 4096;
+
+/// This is the maximum number of sequence numbers in a single range record.
+const MAX_RANGE: u32 = 8192;
+
+/// This is the size of the flags and the record count preceding the records.
+const HEADER_SIZE: usize = 3;
+
+/// Acknowledgement holds the sequence numbers of the datagrams that were received, or that are
+/// known to be missing when it is a negative acknowledgement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Acknowledgement {
+    pub negative: bool,
+    pub sequence_numbers: Vec<u32>,
+}
+
+impl Acknowledgement {
+    /// Creates and returns a new acknowledgement of the sequence numbers.
+    pub fn ack(sequence_numbers: Vec<u32>) -> Self {
+        Self {
+            negative: false,
+            sequence_numbers,
+        }
+    }
+
+    /// Creates and returns a new negative acknowledgement of the sequence numbers.
+    pub fn nack(sequence_numbers: Vec<u32>) -> Self {
+        Self {
+            negative: true,
+            sequence_numbers,
+        }
+    }
+
+    /// Encodes the acknowledgement, merging consecutive sequence numbers into ranges. The records
+    /// are spread over as many datagrams as needed for each of them to fit into the maximum size.
+    pub fn encode(&self, max_size: usize) -> Vec<Vec<u8>> {
+        let mut numbers = self.sequence_numbers.clone();
+        numbers.sort_unstable();
+        numbers.dedup();
+
+        let mut records = Vec::new();
+        let mut iter = numbers.into_iter();
+
+        if let Some(first) = iter.next() {
+            let (mut start, mut end) = (first, first);
+
+            for number in iter {
+                if number == end + 1 {
+                    end = number;
+                    continue;
+                }
+
+                records.push((start, end));
+                start = number;
+                end = number;
+            }
+
+            records.push((start, end));
+        }
+
+        let flags = FLAG_VALID | if self.negative { FLAG_NACK } else { FLAG_ACK };
+
+        let mut datagrams = Vec::new();
+        let mut buf = Vec::new();
+        let mut count: u16 = 0;
+
+        for (start, end) in records {
+            let size = if start == end { 4 } else { 7 };
+            if count == u16::MAX || (count > 0 && HEADER_SIZE + buf.len() + size > max_size) {
+                datagrams.push(seal(flags, count, &buf));
+                buf.clear();
+                count = 0;
+            }
+
+            if start == end {
+                buf.push(1);
+                buf.write_u24::<LE>(start).unwrap();
+            } else {
+                buf.push(0);
+                buf.write_u24::<LE>(start).unwrap();
+                buf.write_u24::<LE>(end).unwrap();
+            }
+            count += 1;
+        }
+
+        if count > 0 {
+            datagrams.push(seal(flags, count, &buf));
+        }
+
+        datagrams
+    }
+
+    /// Decodes an acknowledgement.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut buf = data;
+        let flags = buf.read_u8()?;
+
+        let count = buf.read_u16::<BE>()?.min(MAX_RECORDS);
+        let mut sequence_numbers = Vec::new();
+
+        for _ in 0..count {
+            let single = buf.read_u8()? != 0;
+            let start = buf.read_u24::<LE>()?;
+
+            if single {
+                sequence_numbers.push(start);
+                continue;
+            }
+
+            // The range may wrap around to zero, in which case its end is below its start.
+            let end = buf.read_u24::<LE>()?;
+            let len = serial::distance(start, end).min(MAX_RANGE);
+
+            let mut number = start;
+            for _ in 0..=len {
+                sequence_numbers.push(number);
+                number = serial::next(number);
+            }
+        }
+
+        Ok(Self {
+            negative: flags & FLAG_NACK != 0,
+            sequence_numbers,
+        })
+    }
+}
+
+/// Encodes a datagram holding the records, prefixed with the flags and the number of records.
+fn seal(flags: u8, count: u16, records: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(HEADER_SIZE + records.len());
+    buf.push(flags);
+    buf.write_u16::<BE>(count).unwrap();
+    buf.extend_from_slice(records);
+    buf
+}
@@ -0,0 +1,72 @@
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6},
+};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, BE, LE};
+
+use crate::error::{Error, Result};
+
+/// This is the address family of IPv6 addresses as encoded by RakNet.
+const AF_INET6: u16 = 23;
+
+/// Reads a socket address in the format used by RakNet. The bytes of IPv4 addresses are inverted.
+pub fn read_address<R: Read>(buf: &mut R) -> Result<SocketAddr> {
+    match buf.read_u8()? {
+        4 => {
+            let mut ip = [0u8; 4];
+            buf.read_exact(&mut ip)?;
+
+            let ip = Ipv4Addr::from(ip.map(|b| !b));
+            let port = buf.read_u16::<BE>()?;
+
+            Ok(SocketAddr::new(IpAddr::V4(ip), port))
+        }
+        6 => {
+            buf.read_u16::<LE>()?;
+            let port = buf.read_u16::<BE>()?;
+            let flow_info = buf.read_u32::<BE>()?;
+
+            let mut ip = [0u8; 16];
+            buf.read_exact(&mut ip)?;
+            let scope_id = buf.read_u32::<BE>()?;
+
+            Ok(SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(ip),
+                port,
+                flow_info,
+                scope_id,
+            )))
+        }
+        version => Err(Error::InvalidDiscriminant {
+            ty: "IpVersion",
+            value: version as i64,
+        }),
+    }
+}
+
+/// Writes a socket address in the format used by RakNet.
+pub fn write_address<W: Write>(buf: &mut W, addr: &SocketAddr) -> Result<()> {
+    match addr {
+        SocketAddr::V4(addr) => {
+            buf.write_u8(4)?;
+            buf.write_all(&addr.ip().octets().map(|b| !b))?;
+            buf.write_u16::<BE>(addr.port())?;
+        }
+        SocketAddr::V6(addr) => {
+            buf.write_u8(6)?;
+            buf.write_u16::<LE>(AF_INET6)?;
+            buf.write_u16::<BE>(addr.port())?;
+            buf.write_u32::<BE>(addr.flowinfo())?;
+            buf.write_all(&addr.ip().octets())?;
+            buf.write_u32::<BE>(addr.scope_id())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the placeholder address used to fill the system addresses of the connection messages.
+pub fn unspecified_address() -> SocketAddr {
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{Ipv4Addr, SocketAddr},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    net::{lookup_host, ToSocketAddrs, UdpSocket},
+    time::timeout,
+};
+
+use crate::{
+    error::{Error, Result},
+    MINECRAFT_VERSION, PROTOCOL_VERSION,
+};
+
+use super::{connection::recv_offline, frame::FLAG_VALID, message::Message};
+
+/// This is the port on which Minecraft looks for servers in the local network.
+pub const LAN_DISCOVERY_PORT: u16 = 19132;
+
+/// This is the edition advertised by Minecraft: Bedrock Edition servers.
+pub const EDITION_BEDROCK: &str = "MCPE";
+
+/// This is the edition advertised by Minecraft: Education Edition servers.
+pub const EDITION_EDUCATION: &str = "MCEE";
+
+/// This is the number of fields required in an advertisement.
+const REQUIRED_FIELDS: usize = 6;
+
+/// ServerAdvertisement is the information of a server shown in the server list of the client. It is
+/// sent as the data of the unconnected pong, in the format of a semicolon separated string:
+/// `MCPE;motd;protocol;version;online;max;guid;sub-motd;game mode;game mode id;port;port v6;`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerAdvertisement {
+    pub edition: String,
+    pub motd: String,
+    pub protocol: i32,
+    pub version: String,
+    pub players_online: u32,
+    pub max_players: u32,
+    pub server_guid: i64,
+    pub sub_motd: String,
+    pub game_mode: String,
+    pub game_mode_id: i32,
+    pub port_v4: Option<u16>,
+    pub port_v6: Option<u16>,
+}
+
+impl Default for ServerAdvertisement {
+    fn default() -> Self {
+        Self {
+            edition: EDITION_BEDROCK.into(),
+            motd: "Dedicated Server".into(),
+            protocol: PROTOCOL_VERSION as i32,
+            version: MINECRAFT_VERSION.into(),
+            players_online: 0,
+            max_players: 20,
+            server_guid: 0,
+            sub_motd: "Bedrock level".into(),
+            game_mode: "Survival".into(),
+            game_mode_id: 1,
+            port_v4: None,
+            port_v6: None,
+        }
+    }
+}
+
+impl ServerAdvertisement {
+    /// Creates and returns a new advertisement with the MOTD, advertising the protocol and
+    /// Minecraft version supported by this library.
+    pub fn new(motd: impl Into<String>) -> Self {
+        Self {
+            motd: motd.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if the server has slots open for new players.
+    pub fn has_open_slots(&self) -> bool {
+        self.players_online < self.max_players
+    }
+}
+
+impl fmt::Display for ServerAdvertisement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{};{};{};{};{};{};{};{};{};{};",
+            self.edition,
+            escape(&self.motd),
+            self.protocol,
+            self.version,
+            self.players_online,
+            self.max_players,
+            self.server_guid,
+            escape(&self.sub_motd),
+            self.game_mode,
+            self.game_mode_id,
+        )?;
+
+        if self.port_v4.is_some() || self.port_v6.is_some() {
+            write!(
+                f,
+                "{};{};",
+                self.port_v4.unwrap_or_default(),
+                self.port_v6.unwrap_or_default()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for ServerAdvertisement {
+    type Err = Error;
+
+    /// Parses the advertisement, only requiring the fields up to the maximum number of players as
+    /// older servers omit the rest of them.
+    fn from_str(s: &str) -> Result<Self> {
+        let fields: Vec<&str> = s.split(';').collect();
+        if fields.len() < REQUIRED_FIELDS {
+            return Err(Error::InvalidAdvertisement(format!(
+                "expected at least {} fields, found {}",
+                REQUIRED_FIELDS,
+                fields.len()
+            )));
+        }
+
+        let field = |index: usize| fields.get(index).copied().filter(|f| !f.is_empty());
+        let defaults = Self::default();
+
+        Ok(Self {
+            edition: fields[0].into(),
+            motd: fields[1].into(),
+            protocol: parse(fields[2], "protocol")?,
+            version: fields[3].into(),
+            players_online: parse(fields[4], "players online")?,
+            max_players: parse(fields[5], "max players")?,
+            server_guid: field(6).map_or(Ok(0), |f| parse(f, "server guid"))?,
+            sub_motd: field(7).unwrap_or_default().into(),
+            game_mode: field(8).map_or(defaults.game_mode, Into::into),
+            game_mode_id: field(9)
+                .map_or(Ok(defaults.game_mode_id), |f| parse(f, "game mode id"))?,
+            port_v4: field(10).map(|f| parse(f, "port")).transpose()?,
+            port_v6: field(11).map(|f| parse(f, "port v6")).transpose()?,
+        })
+    }
+}
+
+/// Semicolons separate the fields of the advertisement, so they may not appear in its text.
+fn escape(text: &str) -> String {
+    text.replace(';', ":")
+}
+
+fn parse<T: FromStr>(field: &str, name: &str) -> Result<T> {
+    field
+        .trim()
+        .parse()
+        .map_err(|_| Error::InvalidAdvertisement(format!("invalid {}: {:?}", name, field)))
+}
+
+/// Pings the server at the address and returns its advertisement.
+pub async fn ping(addr: impl ToSocketAddrs, wait: Duration) -> Result<ServerAdvertisement> {
+    let peer = lookup_host(addr)
+        .await?
+        .next()
+        .ok_or_else(|| Error::RakNet("address did not resolve".into()))?;
+
+    let local = if peer.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.send_to(&unconnected_ping(false), peer).await?;
+
+    let reply = timeout(wait, async {
+        loop {
+            if let Message::UnconnectedPong { data, .. } = recv_offline(&socket, peer).await? {
+                return data.parse::<ServerAdvertisement>();
+            }
+        }
+    });
+
+    reply.await.map_err(|_| Error::Timeout)?
+}
+
+/// Broadcasts an unconnected ping to the local network, the same way Minecraft discovers LAN
+/// games, and returns the advertisements of the servers that replied in time.
+pub async fn discover(wait: Duration) -> Result<Vec<(SocketAddr, ServerAdvertisement)>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.set_broadcast(true)?;
+    socket
+        .send_to(
+            &unconnected_ping(true),
+            (Ipv4Addr::BROADCAST, LAN_DISCOVERY_PORT),
+        )
+        .await?;
+
+    let deadline = Instant::now() + wait;
+    let mut servers = HashMap::new();
+    let mut buf = [0u8; 2048];
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let Ok(received) = timeout(remaining, socket.recv_from(&mut buf)).await else {
+            break;
+        };
+
+        let (len, addr) = received?;
+        if len == 0 || buf[0] & FLAG_VALID != 0 {
+            continue;
+        }
+
+        if let Ok(Message::UnconnectedPong { data, .. }) = Message::decode(&buf[..len]) {
+            if let Ok(advertisement) = data.parse() {
+                servers.insert(addr, advertisement);
+            }
+        }
+    }
+
+    Ok(servers.into_iter().collect())
+}
+
+fn unconnected_ping(open_connections: bool) -> Vec<u8> {
+    Message::UnconnectedPing {
+        time: 0,
+        client_guid: rand::random(),
+        open_connections,
+    }
+    .encode()
+}
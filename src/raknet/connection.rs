@@ -0,0 +1,654 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{debug, warn};
+use tokio::{
+    net::{lookup_host, ToSocketAddrs, UdpSocket},
+    sync::mpsc::{self, error::TrySendError},
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
+use tokio_bytes::BytesMut;
+use tokio_util::codec::Encoder;
+
+use crate::{
+    error::{Error, Result},
+    packet::{batch::Batch, codec::GameCodec, GamePacket},
+};
+
+use super::{
+    advertisement::ServerAdvertisement,
+    frame::{Reliability, FLAG_VALID},
+    message::Message,
+    session::Session,
+    MAX_MTU_SIZE, MIN_MTU_SIZE, RAKNET_PROTOCOL_VERSION, TICK_INTERVAL,
+};
+
+/// This is the size of the buffer datagrams are read into, which fits any MTU size.
+const RECEIVE_BUFFER_SIZE: usize = 2048;
+
+/// This is the number of datagrams buffered for a connection before new ones are dropped.
+const INBOUND_CAPACITY: usize = 512;
+
+/// This is the number of connections buffered by the listener until they are accepted.
+const ACCEPT_BACKLOG: usize = 64;
+
+/// These are the MTU sizes tried by the dialer, from the largest to the smallest.
+const DIAL_MTU_SIZES: [u16; 3] = [MAX_MTU_SIZE, 1200, MIN_MTU_SIZE];
+
+/// This is the number of times an offline request is sent before moving on.
+const DIAL_ATTEMPTS: usize = 4;
+
+/// This is the duration the dialer waits for the reply to an offline request.
+const DIAL_RETRY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// This is the interval at which connected pings are sent to keep the connection alive.
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Connection is a RakNet connection carrying Minecraft game packets. The frames of the game
+/// packets are encoded and decoded by the [`GameCodec`] of the connection, so compression and
+/// encryption are applied once enabled on it.
+///
+/// The connection does not spawn a task of its own, the datagrams are processed and the pending
+/// frames are flushed while [`Connection::read_packet`] is awaited, so it should be awaited
+/// continuously for the connection to stay alive.
+pub struct Connection {
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,
+    guid: i64,
+    inbound: mpsc::Receiver<Vec<u8>>,
+    reader: Option<JoinHandle<()>>,
+    session: Session,
+    codec: GameCodec,
+    packets: VecDeque<Result<GamePacket>>,
+    epoch: Instant,
+    last_ping: Instant,
+    connected: bool,
+    closed: bool,
+}
+
+impl Connection {
+    fn new(
+        socket: Arc<UdpSocket>,
+        peer: SocketAddr,
+        guid: i64,
+        mtu: u16,
+        inbound: mpsc::Receiver<Vec<u8>>,
+    ) -> Result<Self> {
+        let now = Instant::now();
+
+        Ok(Self {
+            socket,
+            peer,
+            guid,
+            inbound,
+            reader: None,
+            session: Session::new(mtu, now)?,
+            codec: GameCodec::new(),
+            packets: VecDeque::new(),
+            epoch: now,
+            last_ping: now,
+            connected: false,
+            closed: false,
+        })
+    }
+
+    /// Connects to the RakNet server at the address, discovering the largest MTU size supported
+    /// by the network path.
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        let peer = lookup_host(addr)
+            .await?
+            .next()
+            .ok_or_else(|| Error::RakNet("address did not resolve".into()))?;
+
+        let local = if peer.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = Arc::new(UdpSocket::bind(local).await?);
+
+        let guid = rand::random();
+        let mtu = open_connection(&socket, peer, guid).await?;
+
+        let (tx, rx) = mpsc::channel(INBOUND_CAPACITY);
+        let reader = tokio::spawn(read_datagrams(socket.clone(), peer, tx));
+
+        let mut conn = Self::new(socket, peer, guid, mtu, rx)?;
+        conn.reader = Some(reader);
+
+        conn.send_message(
+            &Message::ConnectionRequest {
+                client_guid: guid,
+                time: conn.time(),
+            },
+            Reliability::Reliable,
+        );
+        conn.flush().await?;
+
+        while !conn.connected {
+            conn.poll().await?;
+        }
+
+        Ok(conn)
+    }
+
+    /// Returns the address of the other end of the connection.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Returns the GUID of this end of the connection.
+    pub fn guid(&self) -> i64 {
+        self.guid
+    }
+
+    /// Returns the negotiated MTU size.
+    pub fn mtu(&self) -> u16 {
+        self.session.mtu()
+    }
+
+    /// Returns the codec used for the game packet frames.
+    pub fn codec(&self) -> &GameCodec {
+        &self.codec
+    }
+
+    /// Returns the codec used for the game packet frames, to enable the encryption or change the
+    /// protocol version.
+    pub fn codec_mut(&mut self) -> &mut GameCodec {
+        &mut self.codec
+    }
+
+    /// Returns true if the connection has been closed by either end.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Reads the next game packet sent by the other end of the connection. A packet that could not
+    /// be decoded is returned as an error, after which the following packets may still be read.
+    pub async fn read_packet(&mut self) -> Result<GamePacket> {
+        loop {
+            if let Some(packet) = self.packets.pop_front() {
+                return packet;
+            }
+
+            self.poll().await?;
+        }
+    }
+
+    /// Writes the game packet to the connection in a frame of its own.
+    pub async fn write_packet(&mut self, packet: impl Into<GamePacket>) -> Result<()> {
+        let mut frame = BytesMut::new();
+        Encoder::<GamePacket>::encode(&mut self.codec, packet.into(), &mut frame)?;
+
+        self.write_frame(&frame).await
+    }
+
+    /// Writes the game packets of the batch to the connection in a single frame.
+    pub async fn write_batch(&mut self, batch: Batch) -> Result<()> {
+        let mut frame = BytesMut::new();
+        Encoder::<Batch>::encode(&mut self.codec, batch, &mut frame)?;
+
+        self.write_frame(&frame).await
+    }
+
+    async fn write_frame(&mut self, frame: &[u8]) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed);
+        }
+
+        self.session.send(frame, Reliability::ReliableOrdered, 0);
+        self.flush().await
+    }
+
+    /// Notifies the other end of the connection that it is being closed.
+    pub async fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        self.send_message(
+            &Message::DisconnectNotification,
+            Reliability::ReliableOrdered,
+        );
+        self.closed = true;
+        self.flush().await
+    }
+
+    /// Waits for the next datagram, or for the next tick if none arrives in time, and processes
+    /// it.
+    async fn poll(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed);
+        }
+
+        tokio::select! {
+            datagram = self.inbound.recv() => match datagram {
+                Some(datagram) => self.handle_datagram(&datagram).await,
+                None => {
+                    self.closed = true;
+                    Err(Error::Closed)
+                }
+            },
+            _ = sleep(TICK_INTERVAL) => self.tick().await,
+        }
+    }
+
+    async fn handle_datagram(&mut self, datagram: &[u8]) -> Result<()> {
+        let messages = match self.session.handle(datagram, Instant::now()) {
+            Ok(messages) => messages,
+            Err(err) => {
+                warn!("Dropping datagram from {}: {}", self.peer, err);
+                return Ok(());
+            }
+        };
+
+        for message in messages {
+            match Message::decode(&message) {
+                Ok(message) => self.handle_message(message)?,
+                Err(err) => warn!("Dropping message from {}: {}", self.peer, err),
+            }
+        }
+
+        self.flush().await
+    }
+
+    fn handle_message(&mut self, message: Message) -> Result<()> {
+        match message {
+            Message::ConnectedPing { time } => {
+                let pong = Message::ConnectedPong {
+                    ping_time: time,
+                    pong_time: self.time(),
+                };
+                self.send_message(&pong, Reliability::Unreliable);
+            }
+            Message::ConnectedPong { .. } => {}
+            Message::ConnectionRequest { time, .. } => {
+                let accepted = Message::ConnectionRequestAccepted {
+                    client_address: self.peer,
+                    request_time: time,
+                    time: self.time(),
+                };
+                self.send_message(&accepted, Reliability::Reliable);
+            }
+            Message::ConnectionRequestAccepted { time, .. } => {
+                let incoming = Message::NewIncomingConnection {
+                    server_address: self.peer,
+                    request_time: time,
+                    time: self.time(),
+                };
+                self.send_message(&incoming, Reliability::ReliableOrdered);
+                self.connected = true;
+            }
+            Message::NewIncomingConnection { .. } => self.connected = true,
+            Message::DisconnectNotification => self.closed = true,
+            Message::Game(frame) => {
+                let packets = self.codec.decode_frame(&frame)?;
+                self.packets.extend(packets);
+            }
+            message => debug!(
+                "Ignoring unexpected message from {}: {:?}",
+                self.peer, message
+            ),
+        }
+
+        Ok(())
+    }
+
+    async fn tick(&mut self) -> Result<()> {
+        let now = Instant::now();
+        if self.session.timed_out(now) {
+            self.closed = true;
+            return Err(Error::Timeout);
+        }
+
+        if self.connected && now.duration_since(self.last_ping) >= PING_INTERVAL {
+            self.last_ping = now;
+            self.send_message(
+                &Message::ConnectedPing { time: self.time() },
+                Reliability::Unreliable,
+            );
+        }
+
+        self.flush().await
+    }
+
+    fn send_message(&mut self, message: &Message, reliability: Reliability) {
+        self.session.send(&message.encode(), reliability, 0);
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        for datagram in self.session.flush(Instant::now()) {
+            self.socket.send_to(&datagram, self.peer).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of milliseconds since the connection was opened.
+    fn time(&self) -> i64 {
+        self.epoch.elapsed().as_millis() as i64
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Some(reader) = &self.reader {
+            reader.abort();
+        }
+    }
+}
+
+/// Performs the offline part of the handshake with the server, returning the negotiated MTU size.
+async fn open_connection(socket: &UdpSocket, peer: SocketAddr, guid: i64) -> Result<u16> {
+    let mut reply_mtu = None;
+
+    'sizes: for mtu in DIAL_MTU_SIZES {
+        let request = Message::OpenConnectionRequest1 {
+            protocol: RAKNET_PROTOCOL_VERSION,
+            mtu,
+        };
+
+        for _ in 0..DIAL_ATTEMPTS {
+            socket.send_to(&request.encode(), peer).await?;
+
+            match timeout(DIAL_RETRY_TIMEOUT, recv_offline(socket, peer)).await {
+                Ok(Ok(Message::OpenConnectionReply1 { mtu, .. })) => {
+                    reply_mtu = Some(mtu);
+                    break 'sizes;
+                }
+                Ok(Ok(Message::IncompatibleProtocolVersion { protocol, .. })) => {
+                    return Err(Error::RakNet(format!(
+                        "server uses RakNet protocol version {}",
+                        protocol
+                    )));
+                }
+                Ok(Err(err)) => return Err(err),
+                _ => continue,
+            }
+        }
+    }
+
+    let mtu = reply_mtu
+        .ok_or(Error::Timeout)?
+        .clamp(MIN_MTU_SIZE, MAX_MTU_SIZE);
+
+    let request = Message::OpenConnectionRequest2 {
+        server_address: peer,
+        mtu,
+        client_guid: guid,
+    };
+
+    for _ in 0..DIAL_ATTEMPTS {
+        socket.send_to(&request.encode(), peer).await?;
+
+        match timeout(DIAL_RETRY_TIMEOUT, recv_offline(socket, peer)).await {
+            Ok(Ok(Message::OpenConnectionReply2 { mtu, .. })) => {
+                return Ok(mtu.clamp(MIN_MTU_SIZE, MAX_MTU_SIZE));
+            }
+            Ok(Err(err)) => return Err(err),
+            _ => continue,
+        }
+    }
+
+    Err(Error::Timeout)
+}
+
+/// Waits for the next offline message sent by the peer, ignoring anything else.
+pub(super) async fn recv_offline(socket: &UdpSocket, peer: SocketAddr) -> Result<Message> {
+    let mut buf = [0u8; RECEIVE_BUFFER_SIZE];
+
+    loop {
+        let (len, addr) = socket.recv_from(&mut buf).await?;
+        if addr != peer || len == 0 || buf[0] & FLAG_VALID != 0 {
+            continue;
+        }
+
+        match Message::decode(&buf[..len]) {
+            Ok(message) if message.is_offline() => return Ok(message),
+            Ok(_) => continue,
+            Err(err) => warn!("Dropping offline message from {}: {}", addr, err),
+        }
+    }
+}
+
+/// Forwards the datagrams sent by the peer to the connection until it is dropped.
+async fn read_datagrams(socket: Arc<UdpSocket>, peer: SocketAddr, tx: mpsc::Sender<Vec<u8>>) {
+    let mut buf = [0u8; RECEIVE_BUFFER_SIZE];
+
+    loop {
+        let (len, addr) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(err) => {
+                warn!("Failed to read datagram: {}", err);
+                continue;
+            }
+        };
+
+        if addr != peer || len == 0 || buf[0] & FLAG_VALID == 0 {
+            continue;
+        }
+
+        match tx.try_send(buf[..len].to_vec()) {
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Closed(_)) => return,
+        }
+    }
+}
+
+/// Listener accepts RakNet connections on a UDP socket. It answers the offline pings, including
+/// the ones broadcast by clients looking for LAN games, with the advertisement set using
+/// [`Listener::set_advertisement`].
+pub struct Listener {
+    socket: Arc<UdpSocket>,
+    guid: i64,
+    advertisement: Arc<Mutex<ServerAdvertisement>>,
+    incoming: mpsc::Receiver<Connection>,
+    task: JoinHandle<()>,
+}
+
+impl Listener {
+    /// Binds a new listener to the address.
+    pub async fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        let socket = Arc::new(UdpSocket::bind(addr).await?);
+        let guid = rand::random();
+        let mut advertisement = ServerAdvertisement::default();
+        fill_advertisement(&mut advertisement, guid, socket.local_addr()?);
+
+        let advertisement = Arc::new(Mutex::new(advertisement));
+        let (tx, incoming) = mpsc::channel(ACCEPT_BACKLOG);
+
+        let state = ListenerState {
+            socket: socket.clone(),
+            guid,
+            advertisement: advertisement.clone(),
+            incoming: tx,
+            connections: HashMap::new(),
+        };
+        let task = tokio::spawn(state.run());
+
+        Ok(Self {
+            socket,
+            guid,
+            advertisement,
+            incoming,
+            task,
+        })
+    }
+
+    /// Returns the address the listener is bound to.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Returns the GUID of the listener, which is sent in the offline replies.
+    pub fn guid(&self) -> i64 {
+        self.guid
+    }
+
+    /// Returns the advertisement sent in the replies to offline pings.
+    pub fn advertisement(&self) -> ServerAdvertisement {
+        self.advertisement.lock().unwrap().clone()
+    }
+
+    /// Sets the advertisement sent in the replies to offline pings. The server GUID and the ports
+    /// are filled in from the listener if they are not set.
+    pub fn set_advertisement(&self, mut advertisement: ServerAdvertisement) -> Result<()> {
+        fill_advertisement(&mut advertisement, self.guid, self.local_addr()?);
+        *self.advertisement.lock().unwrap() = advertisement;
+        Ok(())
+    }
+
+    /// Waits for the next connection to complete the offline handshake and returns it.
+    pub async fn accept(&mut self) -> Result<Connection> {
+        self.incoming.recv().await.ok_or(Error::Closed)
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Fills in the fields of the advertisement that are specific to the listener.
+fn fill_advertisement(advertisement: &mut ServerAdvertisement, guid: i64, addr: SocketAddr) {
+    if advertisement.server_guid == 0 {
+        advertisement.server_guid = guid;
+    }
+
+    match addr {
+        SocketAddr::V4(_) => {
+            advertisement.port_v4.get_or_insert(addr.port());
+        }
+        SocketAddr::V6(_) => {
+            advertisement.port_v6.get_or_insert(addr.port());
+        }
+    }
+}
+
+/// ListenerState is owned by the task reading the socket of a listener.
+struct ListenerState {
+    socket: Arc<UdpSocket>,
+    guid: i64,
+    advertisement: Arc<Mutex<ServerAdvertisement>>,
+    incoming: mpsc::Sender<Connection>,
+    connections: HashMap<SocketAddr, mpsc::Sender<Vec<u8>>>,
+}
+
+impl ListenerState {
+    async fn run(mut self) {
+        let mut buf = [0u8; RECEIVE_BUFFER_SIZE];
+
+        loop {
+            let (len, addr) = match self.socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(err) => {
+                    warn!("Failed to read datagram: {}", err);
+                    continue;
+                }
+            };
+
+            if len == 0 {
+                continue;
+            }
+
+            let datagram = &buf[..len];
+            if datagram[0] & FLAG_VALID != 0 {
+                self.forward(addr, datagram);
+                continue;
+            }
+
+            match Message::decode(datagram) {
+                Ok(message) => {
+                    if let Err(err) = self.handle_offline(addr, message).await {
+                        warn!("Failed to handle offline message from {}: {}", addr, err);
+                    }
+                }
+                Err(err) => debug!("Dropping offline message from {}: {}", addr, err),
+            }
+        }
+    }
+
+    fn forward(&mut self, addr: SocketAddr, datagram: &[u8]) {
+        let Some(tx) = self.connections.get(&addr) else {
+            return;
+        };
+
+        if let Err(TrySendError::Closed(_)) = tx.try_send(datagram.to_vec()) {
+            self.connections.remove(&addr);
+        }
+    }
+
+    async fn handle_offline(&mut self, addr: SocketAddr, message: Message) -> Result<()> {
+        let reply = match message {
+            Message::UnconnectedPing {
+                time,
+                open_connections,
+                ..
+            } => {
+                let advertisement = self.advertisement.lock().unwrap().clone();
+
+                // Pings asking for open connections are only answered while slots are available.
+                if open_connections && !advertisement.has_open_slots() {
+                    return Ok(());
+                }
+
+                Message::UnconnectedPong {
+                    time,
+                    server_guid: self.guid,
+                    data: advertisement.to_string(),
+                }
+            }
+            Message::OpenConnectionRequest1 { protocol, .. }
+                if protocol != RAKNET_PROTOCOL_VERSION =>
+            {
+                Message::IncompatibleProtocolVersion {
+                    protocol: RAKNET_PROTOCOL_VERSION,
+                    server_guid: self.guid,
+                }
+            }
+            Message::OpenConnectionRequest1 { mtu, .. } => Message::OpenConnectionReply1 {
+                server_guid: self.guid,
+                mtu: mtu.min(MAX_MTU_SIZE),
+            },
+            Message::OpenConnectionRequest2 { mtu, .. } => {
+                let mtu = mtu.clamp(MIN_MTU_SIZE, MAX_MTU_SIZE);
+                let open = self
+                    .connections
+                    .get(&addr)
+                    .map_or(false, |tx| !tx.is_closed());
+
+                if !open {
+                    let (tx, rx) = mpsc::channel(INBOUND_CAPACITY);
+                    let conn = Connection::new(self.socket.clone(), addr, self.guid, mtu, rx)?;
+
+                    if self.incoming.try_send(conn).is_err() {
+                        return Err(Error::RakNet("accept backlog is full".into()));
+                    }
+                    self.connections.insert(addr, tx);
+                }
+
+                Message::OpenConnectionReply2 {
+                    server_guid: self.guid,
+                    client_address: addr,
+                    mtu,
+                }
+            }
+            message => {
+                debug!(
+                    "Ignoring unexpected offline message from {}: {:?}",
+                    addr, message
+                );
+                return Ok(());
+            }
+        };
+
+        self.socket.send_to(&reply.encode(), addr).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use std::io::Write;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, BE, LE};
+
+use crate::error::{Error, Result};
+
+use super::serial;
+
+/// This is the flag set on every datagram.
+pub const FLAG_VALID: u8 = 0x80;
+
+/// This is the flag set on datagrams holding acknowledgements.
+pub const FLAG_ACK: u8 = 0x40;
+
+/// This is the flag set on datagrams holding negative acknowledgements.
+pub const FLAG_NACK: u8 = 0x20;
+
+/// This is the flag set on datagrams that are part of a continuous send.
+pub const FLAG_CONTINUOUS_SEND: u8 = 0x08;
+
+/// This is the flag set on frames that are a fragment of a split message.
+const FLAG_SPLIT: u8 = 0x10;
+
+/// This is the size of the datagram header, composed of the flags and the sequence number.
+pub const DATAGRAM_HEADER_SIZE: usize = 4;
+
+/// This is the maximum size of the body of a frame, whose length is encoded in bits as a u16.
+/// Frames never come close to it, as their datagram has to fit into the MTU size.
+pub const MAX_BODY_SIZE: usize = u16::MAX as usize / 8;
+
+/// Reliability decides how a frame is delivered to the other end of the connection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Reliability {
+    /// The frame may be lost or arrive out of order.
+    Unreliable,
+    /// The frame may be lost, and is dropped if a newer frame of the channel arrived first.
+    UnreliableSequenced,
+    /// The frame is sent again until it is acknowledged, but may arrive out of order.
+    Reliable,
+    /// The frame is sent again until it is acknowledged, and is delivered in order.
+    #[default]
+    ReliableOrdered,
+    /// The frame is sent again until it is acknowledged, and is dropped if a newer frame of the
+    /// channel arrived first.
+    ReliableSequenced,
+}
+
+impl Reliability {
+    /// Returns the reliability matching the ID in the flags of a frame. The variants asking for an
+    /// acknowledgement receipt are treated as their plain counterparts.
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 | 5 => Ok(Self::Unreliable),
+            1 => Ok(Self::UnreliableSequenced),
+            2 | 6 => Ok(Self::Reliable),
+            3 | 7 => Ok(Self::ReliableOrdered),
+            4 => Ok(Self::ReliableSequenced),
+            id => Err(Error::InvalidDiscriminant {
+                ty: "Reliability",
+                value: id as i64,
+            }),
+        }
+    }
+
+    /// Returns true if the frame is sent again until it is acknowledged.
+    pub fn reliable(&self) -> bool {
+        matches!(
+            self,
+            Self::Reliable | Self::ReliableOrdered | Self::ReliableSequenced
+        )
+    }
+
+    /// Returns true if the frame carries a sequenced index.
+    pub fn sequenced(&self) -> bool {
+        matches!(self, Self::UnreliableSequenced | Self::ReliableSequenced)
+    }
+
+    /// Returns true if the frame carries an ordered index and channel.
+    pub fn ordered(&self) -> bool {
+        matches!(
+            self,
+            Self::UnreliableSequenced | Self::ReliableOrdered | Self::ReliableSequenced
+        )
+    }
+}
+
+/// Split holds the information of a frame that is a fragment of a split message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Split {
+    pub count: u32,
+    pub id: u16,
+    pub index: u32,
+}
+
+/// Frame is a message, or a fragment of one, sent inside a datagram.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub reliability: Reliability,
+    pub reliable_index: u32,
+    pub sequenced_index: u32,
+    pub ordered_index: u32,
+    pub channel: u8,
+    pub split: Option<Split>,
+    pub body: Vec<u8>,
+}
+
+impl Frame {
+    /// Returns the size of the header of the frame with the provided reliability.
+    pub fn header_size(reliability: Reliability, split: bool) -> usize {
+        let mut size = 3;
+        if reliability.reliable() {
+            size += 3;
+        }
+        if reliability.sequenced() {
+            size += 3;
+        }
+        if reliability.ordered() {
+            size += 4;
+        }
+        if split {
+            size += 10;
+        }
+        size
+    }
+
+    /// Returns the encoded size of the frame.
+    pub fn size(&self) -> usize {
+        Self::header_size(self.reliability, self.split.is_some()) + self.body.len()
+    }
+
+    /// Encodes the frame. The body may not be larger than [`MAX_BODY_SIZE`].
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        assert!(
+            self.body.len() <= MAX_BODY_SIZE,
+            "frame body of {} bytes exceeds the maximum of {}",
+            self.body.len(),
+            MAX_BODY_SIZE
+        );
+
+        let mut flags = (self.reliability as u8) << 5;
+        if self.split.is_some() {
+            flags |= FLAG_SPLIT;
+        }
+
+        buf.push(flags);
+        buf.write_u16::<BE>((self.body.len() * 8) as u16).unwrap();
+
+        if self.reliability.reliable() {
+            buf.write_u24::<LE>(self.reliable_index & serial::MASK)
+                .unwrap();
+        }
+        if self.reliability.sequenced() {
+            buf.write_u24::<LE>(self.sequenced_index & serial::MASK)
+                .unwrap();
+        }
+        if self.reliability.ordered() {
+            buf.write_u24::<LE>(self.ordered_index & serial::MASK)
+                .unwrap();
+            buf.push(self.channel);
+        }
+        if let Some(split) = &self.split {
+            buf.write_u32::<BE>(split.count).unwrap();
+            buf.write_u16::<BE>(split.id).unwrap();
+            buf.write_u32::<BE>(split.index).unwrap();
+        }
+
+        buf.write_all(&self.body).unwrap();
+    }
+
+    /// Decodes a frame from the buffer, advancing it past the frame.
+    pub fn read(buf: &mut &[u8]) -> Result<Self> {
+        let flags = buf.read_u8()?;
+        let reliability = Reliability::from_id(flags >> 5)?;
+        let len = (buf.read_u16::<BE>()? as usize + 7) / 8;
+
+        let mut frame = Frame {
+            reliability,
+            ..Default::default()
+        };
+
+        if reliability.reliable() {
+            frame.reliable_index = buf.read_u24::<LE>()?;
+        }
+        if reliability.sequenced() {
+            frame.sequenced_index = buf.read_u24::<LE>()?;
+        }
+        if reliability.ordered() {
+            frame.ordered_index = buf.read_u24::<LE>()?;
+            frame.channel = buf.read_u8()?;
+        }
+        if flags & FLAG_SPLIT != 0 {
+            frame.split = Some(Split {
+                count: buf.read_u32::<BE>()?,
+                id: buf.read_u16::<BE>()?,
+                index: buf.read_u32::<BE>()?,
+            });
+        }
+
+        if len > buf.len() {
+            return Err(Error::OversizePrefix {
+                len,
+                max: buf.len(),
+            });
+        }
+
+        let (body, rest) = buf.split_at(len);
+        frame.body = body.to_vec();
+        *buf = rest;
+
+        Ok(frame)
+    }
+}
+
+/// Datagram is a set of frames sent in a single UDP datagram, identified by a sequence number.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Datagram {
+    pub sequence_number: u32,
+    pub frames: Vec<Frame>,
+}
+
+impl Datagram {
+    /// Returns the encoded size of the datagram.
+    pub fn size(&self) -> usize {
+        DATAGRAM_HEADER_SIZE + self.frames.iter().map(Frame::size).sum::<usize>()
+    }
+
+    /// Encodes the datagram.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.size());
+        buf.push(FLAG_VALID);
+        buf.write_u24::<LE>(self.sequence_number & serial::MASK)
+            .unwrap();
+
+        for frame in &self.frames {
+            frame.write(&mut buf);
+        }
+
+        buf
+    }
+
+    /// Decodes a datagram which is neither an acknowledgement nor a negative acknowledgement.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut buf = data;
+        buf.read_u8()?;
+
+        let sequence_number = buf.read_u24::<LE>()?;
+        let mut frames = Vec::new();
+
+        while !buf.is_empty() {
+            frames.push(Frame::read(&mut buf)?);
+        }
+
+        Ok(Self {
+            sequence_number,
+            frames,
+        })
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    io::{Read, Write},
+    net::SocketAddr,
+};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::{
+    error::{Error, Result},
+    packet::batch::GAME_PACKET_ID,
+};
+
+use super::{
+    address::{read_address, unspecified_address, write_address},
+    MAGIC, UDP_HEADER_SIZE,
+};
+
+pub const ID_CONNECTED_PING: u8 = 0x00;
+pub const ID_UNCONNECTED_PING: u8 = 0x01;
+pub const ID_UNCONNECTED_PING_OPEN_CONNECTIONS: u8 = 0x02;
+pub const ID_CONNECTED_PONG: u8 = 0x03;
+pub const ID_OPEN_CONNECTION_REQUEST_1: u8 = 0x05;
+pub const ID_OPEN_CONNECTION_REPLY_1: u8 = 0x06;
+pub const ID_OPEN_CONNECTION_REQUEST_2: u8 = 0x07;
+pub const ID_OPEN_CONNECTION_REPLY_2: u8 = 0x08;
+pub const ID_CONNECTION_REQUEST: u8 = 0x09;
+pub const ID_CONNECTION_REQUEST_ACCEPTED: u8 = 0x10;
+pub const ID_NEW_INCOMING_CONNECTION: u8 = 0x13;
+pub const ID_DISCONNECT_NOTIFICATION: u8 = 0x15;
+pub const ID_INCOMPATIBLE_PROTOCOL_VERSION: u8 = 0x19;
+pub const ID_UNCONNECTED_PONG: u8 = 0x1c;
+
+/// This is the number of system addresses sent in the connection messages. The RakNet of Bedrock
+/// Edition sends 20 instead of the 10 of upstream RakNet, but they are decoded whatever their
+/// number, as they are only padding.
+const SYSTEM_ADDRESSES: usize = 20;
+
+/// Message is a RakNet message. Offline messages are sent as raw datagrams before a connection is
+/// established, while the other messages are sent as the content of frames.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    ConnectedPing {
+        time: i64,
+    },
+    UnconnectedPing {
+        time: i64,
+        client_guid: i64,
+        /// OpenConnections is true if the ping only expects a reply from servers with open slots.
+        open_connections: bool,
+    },
+    ConnectedPong {
+        ping_time: i64,
+        pong_time: i64,
+    },
+    OpenConnectionRequest1 {
+        protocol: u8,
+        /// MTU is the size of the datagram including the IP and UDP headers.
+        mtu: u16,
+    },
+    OpenConnectionReply1 {
+        server_guid: i64,
+        mtu: u16,
+    },
+    OpenConnectionRequest2 {
+        server_address: SocketAddr,
+        mtu: u16,
+        client_guid: i64,
+    },
+    OpenConnectionReply2 {
+        server_guid: i64,
+        client_address: SocketAddr,
+        mtu: u16,
+    },
+    ConnectionRequest {
+        client_guid: i64,
+        time: i64,
+    },
+    ConnectionRequestAccepted {
+        client_address: SocketAddr,
+        request_time: i64,
+        time: i64,
+    },
+    NewIncomingConnection {
+        server_address: SocketAddr,
+        request_time: i64,
+        time: i64,
+    },
+    DisconnectNotification,
+    IncompatibleProtocolVersion {
+        protocol: u8,
+        server_guid: i64,
+    },
+    UnconnectedPong {
+        time: i64,
+        server_guid: i64,
+        data: String,
+    },
+    /// Game is a game packet frame, starting with the game packet ID.
+    Game(Vec<u8>),
+}
+
+impl Message {
+    /// Returns true if the message is sent as a raw datagram rather than inside a frame.
+    pub fn is_offline(&self) -> bool {
+        matches!(
+            self,
+            Self::UnconnectedPing { .. }
+                | Self::UnconnectedPong { .. }
+                | Self::OpenConnectionRequest1 { .. }
+                | Self::OpenConnectionReply1 { .. }
+                | Self::OpenConnectionRequest2 { .. }
+                | Self::OpenConnectionReply2 { .. }
+                | Self::IncompatibleProtocolVersion { .. }
+        )
+    }
+
+    /// Encodes the message.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write(&mut buf).unwrap();
+        buf
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Self::ConnectedPing { time } => {
+                buf.write_u8(ID_CONNECTED_PING)?;
+                buf.write_i64::<BE>(*time)?;
+            }
+            Self::UnconnectedPing {
+                time,
+                client_guid,
+                open_connections,
+            } => {
+                buf.write_u8(if *open_connections {
+                    ID_UNCONNECTED_PING_OPEN_CONNECTIONS
+                } else {
+                    ID_UNCONNECTED_PING
+                })?;
+                buf.write_i64::<BE>(*time)?;
+                buf.write_all(&MAGIC)?;
+                buf.write_i64::<BE>(*client_guid)?;
+            }
+            Self::ConnectedPong {
+                ping_time,
+                pong_time,
+            } => {
+                buf.write_u8(ID_CONNECTED_PONG)?;
+                buf.write_i64::<BE>(*ping_time)?;
+                buf.write_i64::<BE>(*pong_time)?;
+            }
+            Self::OpenConnectionRequest1 { protocol, mtu } => {
+                buf.write_u8(ID_OPEN_CONNECTION_REQUEST_1)?;
+                buf.write_all(&MAGIC)?;
+                buf.write_u8(*protocol)?;
+
+                // The request is padded up to the MTU size so that the server can find out the size
+                // of the datagrams that make it through.
+                let size = mtu.saturating_sub(UDP_HEADER_SIZE) as usize;
+                buf.resize(size.max(buf.len()), 0);
+            }
+            Self::OpenConnectionReply1 { server_guid, mtu } => {
+                buf.write_u8(ID_OPEN_CONNECTION_REPLY_1)?;
+                buf.write_all(&MAGIC)?;
+                buf.write_i64::<BE>(*server_guid)?;
+                buf.write_u8(0)?;
+                buf.write_u16::<BE>(*mtu)?;
+            }
+            Self::OpenConnectionRequest2 {
+                server_address,
+                mtu,
+                client_guid,
+            } => {
+                buf.write_u8(ID_OPEN_CONNECTION_REQUEST_2)?;
+                buf.write_all(&MAGIC)?;
+                write_address(buf, server_address)?;
+                buf.write_u16::<BE>(*mtu)?;
+                buf.write_i64::<BE>(*client_guid)?;
+            }
+            Self::OpenConnectionReply2 {
+                server_guid,
+                client_address,
+                mtu,
+            } => {
+                buf.write_u8(ID_OPEN_CONNECTION_REPLY_2)?;
+                buf.write_all(&MAGIC)?;
+                buf.write_i64::<BE>(*server_guid)?;
+                write_address(buf, client_address)?;
+                buf.write_u16::<BE>(*mtu)?;
+                buf.write_u8(0)?;
+            }
+            Self::ConnectionRequest { client_guid, time } => {
+                buf.write_u8(ID_CONNECTION_REQUEST)?;
+                buf.write_i64::<BE>(*client_guid)?;
+                buf.write_i64::<BE>(*time)?;
+                buf.write_u8(0)?;
+            }
+            Self::ConnectionRequestAccepted {
+                client_address,
+                request_time,
+                time,
+            } => {
+                buf.write_u8(ID_CONNECTION_REQUEST_ACCEPTED)?;
+                write_address(buf, client_address)?;
+                buf.write_u16::<BE>(0)?;
+                for _ in 0..SYSTEM_ADDRESSES {
+                    write_address(buf, &unspecified_address())?;
+                }
+                buf.write_i64::<BE>(*request_time)?;
+                buf.write_i64::<BE>(*time)?;
+            }
+            Self::NewIncomingConnection {
+                server_address,
+                request_time,
+                time,
+            } => {
+                buf.write_u8(ID_NEW_INCOMING_CONNECTION)?;
+                write_address(buf, server_address)?;
+                for _ in 0..SYSTEM_ADDRESSES {
+                    write_address(buf, &unspecified_address())?;
+                }
+                buf.write_i64::<BE>(*request_time)?;
+                buf.write_i64::<BE>(*time)?;
+            }
+            Self::DisconnectNotification => {
+                buf.write_u8(ID_DISCONNECT_NOTIFICATION)?;
+            }
+            Self::IncompatibleProtocolVersion {
+                protocol,
+                server_guid,
+            } => {
+                buf.write_u8(ID_INCOMPATIBLE_PROTOCOL_VERSION)?;
+                buf.write_u8(*protocol)?;
+                buf.write_all(&MAGIC)?;
+                buf.write_i64::<BE>(*server_guid)?;
+            }
+            Self::UnconnectedPong {
+                time,
+                server_guid,
+                data,
+            } => {
+                buf.write_u8(ID_UNCONNECTED_PONG)?;
+                buf.write_i64::<BE>(*time)?;
+                buf.write_i64::<BE>(*server_guid)?;
+                buf.write_all(&MAGIC)?;
+                buf.write_u16::<BE>(data.len() as u16)?;
+                buf.write_all(data.as_bytes())?;
+            }
+            Self::Game(frame) => {
+                buf.write_all(frame)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes a message.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut buf = data;
+        let id = buf.read_u8()?;
+
+        let message = match id {
+            ID_CONNECTED_PING => Self::ConnectedPing {
+                time: buf.read_i64::<BE>()?,
+            },
+            ID_UNCONNECTED_PING | ID_UNCONNECTED_PING_OPEN_CONNECTIONS => {
+                let time = buf.read_i64::<BE>()?;
+                read_magic(&mut buf)?;
+
+                Self::UnconnectedPing {
+                    time,
+                    client_guid: buf.read_i64::<BE>()?,
+                    open_connections: id == ID_UNCONNECTED_PING_OPEN_CONNECTIONS,
+                }
+            }
+            ID_CONNECTED_PONG => Self::ConnectedPong {
+                ping_time: buf.read_i64::<BE>()?,
+                pong_time: buf.read_i64::<BE>()?,
+            },
+            ID_OPEN_CONNECTION_REQUEST_1 => {
+                read_magic(&mut buf)?;
+
+                Self::OpenConnectionRequest1 {
+                    protocol: buf.read_u8()?,
+                    mtu: (data.len() as u16).saturating_add(UDP_HEADER_SIZE),
+                }
+            }
+            ID_OPEN_CONNECTION_REPLY_1 => {
+                read_magic(&mut buf)?;
+                let server_guid = buf.read_i64::<BE>()?;
+                buf.read_u8()?;
+
+                Self::OpenConnectionReply1 {
+                    server_guid,
+                    mtu: buf.read_u16::<BE>()?,
+                }
+            }
+            ID_OPEN_CONNECTION_REQUEST_2 => {
+                read_magic(&mut buf)?;
+
+                Self::OpenConnectionRequest2 {
+                    server_address: read_address(&mut buf)?,
+                    mtu: buf.read_u16::<BE>()?,
+                    client_guid: buf.read_i64::<BE>()?,
+                }
+            }
+            ID_OPEN_CONNECTION_REPLY_2 => {
+                read_magic(&mut buf)?;
+
+                Self::OpenConnectionReply2 {
+                    server_guid: buf.read_i64::<BE>()?,
+                    client_address: read_address(&mut buf)?,
+                    mtu: buf.read_u16::<BE>()?,
+                }
+            }
+            ID_CONNECTION_REQUEST => Self::ConnectionRequest {
+                client_guid: buf.read_i64::<BE>()?,
+                time: buf.read_i64::<BE>()?,
+            },
+            ID_CONNECTION_REQUEST_ACCEPTED => {
+                let client_address = read_address(&mut buf)?;
+                buf.read_u16::<BE>()?;
+                let (request_time, time) = read_system_addresses(&mut buf)?;
+
+                Self::ConnectionRequestAccepted {
+                    client_address,
+                    request_time,
+                    time,
+                }
+            }
+            ID_NEW_INCOMING_CONNECTION => {
+                let server_address = read_address(&mut buf)?;
+                let (request_time, time) = read_system_addresses(&mut buf)?;
+
+                Self::NewIncomingConnection {
+                    server_address,
+                    request_time,
+                    time,
+                }
+            }
+            ID_DISCONNECT_NOTIFICATION => Self::DisconnectNotification,
+            ID_INCOMPATIBLE_PROTOCOL_VERSION => {
+                let protocol = buf.read_u8()?;
+                read_magic(&mut buf)?;
+
+                Self::IncompatibleProtocolVersion {
+                    protocol,
+                    server_guid: buf.read_i64::<BE>()?,
+                }
+            }
+            ID_UNCONNECTED_PONG => {
+                let time = buf.read_i64::<BE>()?;
+                let server_guid = buf.read_i64::<BE>()?;
+                read_magic(&mut buf)?;
+
+                let len = buf.read_u16::<BE>()? as usize;
+                let mut data = vec![0u8; len];
+                buf.read_exact(&mut data)?;
+
+                Self::UnconnectedPong {
+                    time,
+                    server_guid,
+                    data: String::from_utf8_lossy(&data).into_owned(),
+                }
+            }
+            GAME_PACKET_ID => Self::Game(data.to_vec()),
+            id => {
+                return Err(Error::InvalidDiscriminant {
+                    ty: "RakNetMessage",
+                    value: id as i64,
+                })
+            }
+        };
+
+        Ok(message)
+    }
+}
+
+fn read_magic(buf: &mut &[u8]) -> Result<()> {
+    let mut magic = [0u8; 16];
+    buf.read_exact(&mut magic)?;
+
+    if magic != MAGIC {
+        return Err(Error::RakNet("invalid offline message magic".into()));
+    }
+
+    Ok(())
+}
+
+/// Reads the system addresses of the connection messages followed by the request time and the
+/// time. Older clients send fewer system addresses, which is why the number of addresses is
+/// derived from the remaining size.
+fn read_system_addresses(buf: &mut &[u8]) -> Result<(i64, i64)> {
+    while buf.len() > 16 {
+        read_address(buf)?;
+    }
+
+    Ok((buf.read_i64::<BE>()?, buf.read_i64::<BE>()?))
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+pub mod ack;
+pub mod address;
+pub mod advertisement;
+pub mod connection;
+pub mod frame;
+pub mod message;
+pub mod serial;
+pub mod session;
+
+/// This is the version of the RakNet protocol used by Minecraft.
+pub const RAKNET_PROTOCOL_VERSION: u8 = 11;
+
+/// This is the magic sequence of bytes identifying the offline messages of RakNet.
+pub const MAGIC: [u8; 16] = [
+    0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, 0x56, 0x78,
+];
+
+/// This is the maximum MTU size negotiated by the clients.
+pub const MAX_MTU_SIZE: u16 = 1492;
+
+/// This is the minimum MTU size allowed by RakNet.
+pub const MIN_MTU_SIZE: u16 = 576;
+
+/// This is the size of the IP and UDP headers, which are part of the MTU size but not of the
+/// datagrams read from the socket.
+pub const UDP_HEADER_SIZE: u16 = 28;
+
+/// This is the number of channels available for ordered and sequenced frames.
+pub const MAX_CHANNELS: usize = 32;
+
+/// This is the duration after which a connection is closed if nothing has been received.
+pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// This is the duration after which an unacknowledged datagram is sent again.
+pub const RESEND_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// This is the interval at which the pending frames and acknowledgements are flushed.
+pub const TICK_INTERVAL: Duration = Duration::from_millis(10);
@@ -0,0 +1,23 @@
+/// This is the mask of the 24-bit sequence numbers and indices of RakNet, which wrap around to zero
+/// once they reach it.
+pub const MASK: u32 = 0xff_ffff;
+
+/// This is the distance beyond which a number is considered to come before another one rather than
+/// after it, which is half of the range of the numbers.
+const HALF: u32 = 0x80_0000;
+
+/// Returns the number following the provided one, wrapping around to zero.
+pub fn next(n: u32) -> u32 {
+    n.wrapping_add(1) & MASK
+}
+
+/// Returns the number of steps needed to go forward from a to b, wrapping around to zero.
+pub fn distance(a: u32, b: u32) -> u32 {
+    b.wrapping_sub(a) & MASK
+}
+
+/// Returns true if a comes before b. The numbers are compared as serial numbers, as described by
+/// RFC 1982, so that the numbers sent right after the wrap around come after the ones sent before.
+pub fn before(a: u32, b: u32) -> bool {
+    a != b && distance(a, b) < HALF
+}
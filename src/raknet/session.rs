@@ -0,0 +1,430 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    time::Instant,
+};
+
+use crate::error::{Error, Result};
+
+use super::{
+    ack::Acknowledgement,
+    frame::{
+        Datagram, Frame, Reliability, Split, DATAGRAM_HEADER_SIZE, FLAG_ACK, FLAG_NACK, FLAG_VALID,
+    },
+    serial, CONNECTION_TIMEOUT, MAX_CHANNELS, MAX_MTU_SIZE, MIN_MTU_SIZE, RESEND_TIMEOUT,
+    UDP_HEADER_SIZE,
+};
+
+/// This is the maximum number of fragments a message may be split into.
+const MAX_SPLIT_COUNT: u32 = 512;
+
+/// This is the maximum number of split messages that may be reassembled at the same time.
+const MAX_CONCURRENT_SPLITS: usize = 16;
+
+/// This is the maximum number of out of order messages held back by an ordering channel.
+const MAX_ORDERING_QUEUE: usize = 1024;
+
+/// This is the maximum number of missing datagrams reported by a single negative acknowledgement.
+const MAX_NACK_GAP: u32 = 512;
+
+/// OrderingChannel holds back the messages of a channel that arrived before the messages
+/// preceding them.
+#[derive(Debug, Default)]
+struct OrderingChannel {
+    expected_index: u32,
+    highest_sequenced_index: Option<u32>,
+    pending: BTreeMap<u32, Vec<u8>>,
+}
+
+/// SplitAssembly holds the fragments of a split message until all of them have arrived.
+#[derive(Debug)]
+struct SplitAssembly {
+    template: Frame,
+    fragments: Vec<Option<Vec<u8>>>,
+    received: u32,
+}
+
+/// Session implements the reliability layer of a RakNet connection without doing any I/O. The
+/// datagrams received from the socket are passed to [`Session::handle`], which returns the
+/// messages that are ready to be processed, and the datagrams returned by [`Session::flush`]
+/// are to be written to the socket.
+#[derive(Debug)]
+pub struct Session {
+    mtu: u16,
+    last_received: Instant,
+
+    next_sequence_number: u32,
+    next_reliable_index: u32,
+    next_ordered_index: [u32; MAX_CHANNELS],
+    next_sequenced_index: [u32; MAX_CHANNELS],
+    next_split_id: u16,
+    queue: VecDeque<Frame>,
+    recovery: BTreeMap<u32, (Instant, Vec<Frame>)>,
+
+    acks: Vec<u32>,
+    nacks: Vec<u32>,
+    expected_sequence_number: u32,
+    reliable_window_start: u32,
+    received_reliable: HashSet<u32>,
+    channels: Vec<OrderingChannel>,
+    splits: HashMap<u16, SplitAssembly>,
+}
+
+impl Session {
+    /// Creates and returns a new Session using the negotiated MTU size, failing if it is outside
+    /// of the range allowed by RakNet.
+    pub fn new(mtu: u16, now: Instant) -> Result<Self> {
+        if !(MIN_MTU_SIZE..=MAX_MTU_SIZE).contains(&mtu) {
+            return Err(Error::RakNet(format!(
+                "MTU size {} is outside of {}..={}",
+                mtu, MIN_MTU_SIZE, MAX_MTU_SIZE
+            )));
+        }
+
+        Ok(Self {
+            mtu,
+            last_received: now,
+            next_sequence_number: 0,
+            next_reliable_index: 0,
+            next_ordered_index: [0; MAX_CHANNELS],
+            next_sequenced_index: [0; MAX_CHANNELS],
+            next_split_id: 0,
+            queue: VecDeque::new(),
+            recovery: BTreeMap::new(),
+            acks: Vec::new(),
+            nacks: Vec::new(),
+            expected_sequence_number: 0,
+            reliable_window_start: 0,
+            received_reliable: HashSet::new(),
+            channels: (0..MAX_CHANNELS)
+                .map(|_| OrderingChannel::default())
+                .collect(),
+            splits: HashMap::new(),
+        })
+    }
+
+    /// Returns the negotiated MTU size.
+    pub fn mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    /// Returns true if nothing has been received for longer than the connection timeout.
+    pub fn timed_out(&self, now: Instant) -> bool {
+        now.duration_since(self.last_received) > CONNECTION_TIMEOUT
+    }
+
+    /// Returns true if there are frames waiting to be sent or acknowledged.
+    pub fn has_pending(&self) -> bool {
+        !self.queue.is_empty() || !self.recovery.is_empty()
+    }
+
+    /// Handles a datagram received from the other end of the connection and returns the messages
+    /// that are ready to be processed, in the order in which they are to be processed.
+    pub fn handle(&mut self, data: &[u8], now: Instant) -> Result<Vec<Vec<u8>>> {
+        let flags = *data.first().ok_or(Error::UnexpectedEof)?;
+        if flags & FLAG_VALID == 0 {
+            return Err(Error::RakNet("datagram is missing the valid flag".into()));
+        }
+
+        self.last_received = now;
+
+        if flags & FLAG_ACK != 0 {
+            for sequence_number in Acknowledgement::decode(data)?.sequence_numbers {
+                self.recovery.remove(&sequence_number);
+            }
+            return Ok(Vec::new());
+        }
+
+        if flags & FLAG_NACK != 0 {
+            for sequence_number in Acknowledgement::decode(data)?.sequence_numbers {
+                if let Some((_, frames)) = self.recovery.remove(&sequence_number) {
+                    self.queue.extend(frames);
+                }
+            }
+            return Ok(Vec::new());
+        }
+
+        let datagram = Datagram::decode(data)?;
+        let sequence_number = datagram.sequence_number;
+
+        self.acks.push(sequence_number);
+        self.nacks.retain(|n| *n != sequence_number);
+
+        if !serial::before(sequence_number, self.expected_sequence_number) {
+            let gap = serial::distance(self.expected_sequence_number, sequence_number);
+            if gap <= MAX_NACK_GAP {
+                let mut missing = self.expected_sequence_number;
+                while missing != sequence_number {
+                    self.nacks.push(missing);
+                    missing = serial::next(missing);
+                }
+            }
+            self.expected_sequence_number = serial::next(sequence_number);
+        }
+
+        let mut messages = Vec::new();
+        for frame in datagram.frames {
+            self.handle_frame(frame, &mut messages)?;
+        }
+
+        Ok(messages)
+    }
+
+    fn handle_frame(&mut self, frame: Frame, messages: &mut Vec<Vec<u8>>) -> Result<()> {
+        if frame.reliability.reliable() && !self.receive_reliable(frame.reliable_index) {
+            return Ok(());
+        }
+
+        let frame = match frame.split {
+            Some(split) => match self.assemble(frame, split)? {
+                Some(frame) => frame,
+                None => return Ok(()),
+            },
+            None => frame,
+        };
+
+        if frame.channel as usize >= MAX_CHANNELS {
+            return Err(Error::RakNet(format!("invalid channel {}", frame.channel)));
+        }
+        let channel = &mut self.channels[frame.channel as usize];
+
+        match frame.reliability {
+            Reliability::UnreliableSequenced | Reliability::ReliableSequenced => {
+                if serial::before(frame.ordered_index, channel.expected_index) {
+                    return Ok(());
+                }
+                if let Some(highest) = channel.highest_sequenced_index {
+                    if !serial::before(highest, frame.sequenced_index) {
+                        return Ok(());
+                    }
+                }
+
+                channel.highest_sequenced_index = Some(frame.sequenced_index);
+                messages.push(frame.body);
+            }
+            Reliability::ReliableOrdered => {
+                if serial::before(frame.ordered_index, channel.expected_index) {
+                    return Ok(());
+                }
+
+                if frame.ordered_index != channel.expected_index {
+                    if channel.pending.len() >= MAX_ORDERING_QUEUE {
+                        return Err(Error::RakNet("too many out of order messages".into()));
+                    }
+                    channel.pending.insert(frame.ordered_index, frame.body);
+                    return Ok(());
+                }
+
+                messages.push(frame.body);
+                channel.expected_index = serial::next(channel.expected_index);
+                channel.highest_sequenced_index = None;
+
+                while let Some(body) = channel.pending.remove(&channel.expected_index) {
+                    messages.push(body);
+                    channel.expected_index = serial::next(channel.expected_index);
+                }
+            }
+            Reliability::Unreliable | Reliability::Reliable => messages.push(frame.body),
+        }
+
+        Ok(())
+    }
+
+    /// Marks the reliable index as received, returning false if it was received before.
+    fn receive_reliable(&mut self, index: u32) -> bool {
+        if serial::before(index, self.reliable_window_start)
+            || !self.received_reliable.insert(index)
+        {
+            return false;
+        }
+
+        while self.received_reliable.remove(&self.reliable_window_start) {
+            self.reliable_window_start = serial::next(self.reliable_window_start);
+        }
+
+        true
+    }
+
+    /// Adds the fragment to its split message, returning the reassembled frame once all the
+    /// fragments have arrived.
+    fn assemble(&mut self, frame: Frame, split: Split) -> Result<Option<Frame>> {
+        if split.count == 0 || split.count > MAX_SPLIT_COUNT || split.index >= split.count {
+            return Err(Error::RakNet(format!(
+                "invalid split fragment {} of {}",
+                split.index, split.count
+            )));
+        }
+
+        if !self.splits.contains_key(&split.id) && self.splits.len() >= MAX_CONCURRENT_SPLITS {
+            return Err(Error::RakNet("too many concurrent split messages".into()));
+        }
+
+        let assembly = self
+            .splits
+            .entry(split.id)
+            .or_insert_with(|| SplitAssembly {
+                template: Frame {
+                    split: None,
+                    body: Vec::new(),
+                    ..frame.clone()
+                },
+                fragments: vec![None; split.count as usize],
+                received: 0,
+            });
+
+        if assembly.fragments.len() != split.count as usize {
+            return Err(Error::RakNet(
+                "split count changed between fragments".into(),
+            ));
+        }
+
+        let fragment = &mut assembly.fragments[split.index as usize];
+        if fragment.is_none() {
+            *fragment = Some(frame.body);
+            assembly.received += 1;
+        }
+
+        if assembly.received < split.count {
+            return Ok(None);
+        }
+
+        let mut frame = std::mem::take(&mut assembly.template);
+        frame.body = std::mem::take(&mut assembly.fragments)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect();
+        self.splits.remove(&split.id);
+
+        Ok(Some(frame))
+    }
+
+    /// Queues the message to be sent with the provided reliability on the channel, splitting it
+    /// into fragments if it does not fit into a single datagram.
+    pub fn send(&mut self, body: &[u8], reliability: Reliability, channel: u8) {
+        let channel = (channel as usize).min(MAX_CHANNELS - 1);
+        let available = (self.mtu - UDP_HEADER_SIZE) as usize - DATAGRAM_HEADER_SIZE;
+
+        let mut template = Frame {
+            reliability,
+            channel: channel as u8,
+            ..Default::default()
+        };
+
+        if reliability.sequenced() {
+            template.ordered_index = self.next_ordered_index[channel];
+            template.sequenced_index = self.next_sequenced_index[channel];
+            self.next_sequenced_index[channel] = serial::next(self.next_sequenced_index[channel]);
+        } else if reliability.ordered() {
+            template.ordered_index = self.next_ordered_index[channel];
+            self.next_ordered_index[channel] = serial::next(self.next_ordered_index[channel]);
+            self.next_sequenced_index[channel] = 0;
+        }
+
+        if body.len() + Frame::header_size(reliability, false) <= available {
+            let mut frame = template;
+            frame.body = body.to_vec();
+            self.push_frame(frame);
+            return;
+        }
+
+        // The fragments of a split message are always sent reliably, as losing a single fragment
+        // would lose the whole message.
+        if !template.reliability.reliable() {
+            template.reliability = match template.reliability {
+                Reliability::UnreliableSequenced => Reliability::ReliableSequenced,
+                _ => Reliability::Reliable,
+            };
+        }
+
+        let fragment_size = available - Frame::header_size(template.reliability, true);
+        let chunks = body.chunks(fragment_size);
+        let count = chunks.len() as u32;
+        let id = self.next_split_id;
+        self.next_split_id = self.next_split_id.wrapping_add(1);
+
+        for (index, chunk) in chunks.enumerate() {
+            let mut frame = template.clone();
+            frame.split = Some(Split {
+                count,
+                id,
+                index: index as u32,
+            });
+            frame.body = chunk.to_vec();
+            self.push_frame(frame);
+        }
+    }
+
+    fn push_frame(&mut self, mut frame: Frame) {
+        if frame.reliability.reliable() {
+            frame.reliable_index = self.next_reliable_index;
+            self.next_reliable_index = serial::next(self.next_reliable_index);
+        }
+
+        self.queue.push_back(frame);
+    }
+
+    /// Returns the datagrams to be written to the socket: the pending acknowledgements, the frames
+    /// that were not acknowledged in time and the queued frames.
+    pub fn flush(&mut self, now: Instant) -> Vec<Vec<u8>> {
+        let mut datagrams = Vec::new();
+        let available = (self.mtu - UDP_HEADER_SIZE) as usize;
+
+        if !self.acks.is_empty() {
+            let ack = Acknowledgement::ack(std::mem::take(&mut self.acks));
+            datagrams.extend(ack.encode(available));
+        }
+        if !self.nacks.is_empty() {
+            let nack = Acknowledgement::nack(std::mem::take(&mut self.nacks));
+            datagrams.extend(nack.encode(available));
+        }
+
+        let expired: Vec<u32> = self
+            .recovery
+            .iter()
+            .filter(|(_, (sent, _))| now.duration_since(*sent) >= RESEND_TIMEOUT)
+            .map(|(sequence_number, _)| *sequence_number)
+            .collect();
+
+        for sequence_number in expired {
+            if let Some((_, frames)) = self.recovery.remove(&sequence_number) {
+                self.queue.extend(frames);
+            }
+        }
+
+        let mut datagram = Datagram::default();
+
+        while let Some(frame) = self.queue.pop_front() {
+            if !datagram.frames.is_empty() && datagram.size() + frame.size() > available {
+                datagrams.push(self.seal(std::mem::take(&mut datagram), now));
+            }
+            datagram.frames.push(frame);
+        }
+
+        if !datagram.frames.is_empty() {
+            datagrams.push(self.seal(datagram, now));
+        }
+
+        datagrams
+    }
+
+    /// Assigns the next sequence number to the datagram, keeping its reliable frames until they
+    /// are acknowledged, and encodes it.
+    fn seal(&mut self, mut datagram: Datagram, now: Instant) -> Vec<u8> {
+        datagram.sequence_number = self.next_sequence_number;
+        self.next_sequence_number = serial::next(self.next_sequence_number);
+
+        let reliable: Vec<Frame> = datagram
+            .frames
+            .iter()
+            .filter(|frame| frame.reliability.reliable())
+            .cloned()
+            .collect();
+
+        if !reliable.is_empty() {
+            self.recovery
+                .insert(datagram.sequence_number, (now, reliable));
+        }
+
+        datagram.encode()
+    }
+}
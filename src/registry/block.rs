@@ -0,0 +1,345 @@
+use std::collections::{BTreeMap, HashMap};
+
+use binary::binary::Binary;
+use byteorder::{WriteBytesExt, LE};
+use nbt::{encoding::NetworkLittleEndian, NBTCompound, NBTTag};
+
+use crate::{
+    data::{block::BlockEntry, Slice},
+    error::{Error, Result},
+    packet::play::StartGame,
+};
+
+/// This is the name of the block the client uses in place of the blocks it does not know.
+pub const UNKNOWN_BLOCK: &str = "minecraft:unknown";
+
+/// This is the runtime ID of the unknown block when the runtime IDs are hashes.
+const UNKNOWN_BLOCK_HASH: u32 = 0xfffffffe;
+
+/// BlockStateValue is the value of a property of a block state. Boolean properties are bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BlockStateValue {
+    Byte(u8),
+    Int(i32),
+    String(String),
+}
+
+/// BlockState is a block along with the values of all of its properties, which is what a runtime ID
+/// refers to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BlockState {
+    pub name: String,
+    pub properties: BTreeMap<String, BlockStateValue>,
+}
+
+impl BlockState {
+    /// Creates and returns a new block state of the block without properties.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the block state with the property set to the value.
+    pub fn with(mut self, property: impl Into<String>, value: BlockStateValue) -> Self {
+        self.properties.insert(property.into(), value);
+        self
+    }
+
+    /// Encodes the block state as little endian NBT, with the name followed by the properties in
+    /// the order of their names. This is the encoding the client hashes the block states with.
+    pub fn canonical_nbt(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        buf.push(10);
+        write_string(&mut buf, "");
+
+        buf.push(8);
+        write_string(&mut buf, "name");
+        write_string(&mut buf, &self.name);
+
+        buf.push(10);
+        write_string(&mut buf, "states");
+        for (property, value) in &self.properties {
+            match value {
+                BlockStateValue::Byte(value) => {
+                    buf.push(1);
+                    write_string(&mut buf, property);
+                    buf.push(*value);
+                }
+                BlockStateValue::Int(value) => {
+                    buf.push(3);
+                    write_string(&mut buf, property);
+                    buf.write_i32::<LE>(*value).unwrap();
+                }
+                BlockStateValue::String(value) => {
+                    buf.push(8);
+                    write_string(&mut buf, property);
+                    write_string(&mut buf, value);
+                }
+            }
+        }
+        buf.push(0);
+
+        buf.push(0);
+        buf
+    }
+
+    /// Returns the runtime ID of the block state when the runtime IDs are hashes, which is the
+    /// 32-bit FNV-1a hash of its canonical NBT.
+    pub fn network_hash(&self) -> u32 {
+        if self.name == UNKNOWN_BLOCK {
+            return UNKNOWN_BLOCK_HASH;
+        }
+
+        fnv1a_32(&self.canonical_nbt())
+    }
+
+    /// Decodes the block states of a file of network little endian NBT compounds holding the name
+    /// and the states of a block one after the other, such as the canonical block states of the
+    /// vanilla blocks.
+    pub fn read_all(data: &[u8]) -> Result<Vec<Self>> {
+        let mut buf = data;
+
+        let mut states = Vec::new();
+        while !buf.is_empty() {
+            let compound = NBTCompound::<NetworkLittleEndian>::deserialize(&mut buf)
+                .map_err(Error::from_io)?;
+            states.push(Self::from_compound(&compound)?);
+        }
+
+        Ok(states)
+    }
+
+    /// Creates a block state from a compound holding its name and states.
+    fn from_compound(compound: &NBTCompound<NetworkLittleEndian>) -> Result<Self> {
+        let name = match compound.get("name") {
+            Some(NBTTag::String(name)) => name,
+            _ => {
+                return Err(Error::InvalidBlockState(
+                    "block state without a name".into(),
+                ))
+            }
+        };
+
+        let mut state = Self::new(name.as_str());
+        if let Some(NBTTag::Compound(properties)) = compound.get("states") {
+            for (property, value) in properties.iter() {
+                state
+                    .properties
+                    .insert(property.clone(), state_value(value)?);
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+/// BlockRegistry maps the block states to the runtime IDs they are sent with, and back. The runtime
+/// IDs are either the indices of the block states sorted by the hash of their names, or the hashes
+/// of the block states if the StartGame packet enabled block network ID hashes.
+#[derive(Debug, Default)]
+pub struct BlockRegistry {
+    hashed: bool,
+    states: Vec<BlockState>,
+    runtime_ids: HashMap<BlockState, u32>,
+    indices: HashMap<u32, usize>,
+}
+
+impl BlockRegistry {
+    /// Creates and returns a new registry of the block states. The states are sorted by the 64-bit
+    /// FNV-1 hash of their names, keeping the order of the states of each block.
+    pub fn new(mut states: Vec<BlockState>, hashed: bool) -> Result<Self> {
+        states.sort_by_cached_key(|state| fnv1_64(state.name.as_bytes()));
+
+        let mut runtime_ids = HashMap::with_capacity(states.len());
+        let mut indices = HashMap::with_capacity(states.len());
+
+        for (index, state) in states.iter().enumerate() {
+            let runtime_id = match hashed {
+                true => state.network_hash(),
+                false => index as u32,
+            };
+
+            if runtime_ids.insert(state.clone(), runtime_id).is_some() {
+                return Err(Error::InvalidBlockState(format!(
+                    "duplicate block state of {}",
+                    state.name
+                )));
+            }
+            if let Some(other) = indices.insert(runtime_id, index) {
+                let other: &BlockState = &states[other];
+                return Err(Error::InvalidBlockState(format!(
+                    "hash {} of {} collides with {}",
+                    runtime_id, state.name, other.name
+                )));
+            }
+        }
+
+        Ok(Self {
+            hashed,
+            states,
+            runtime_ids,
+            indices,
+        })
+    }
+
+    /// Creates and returns a new registry of the vanilla block states along with the states of the
+    /// custom blocks of the StartGame packet, using the runtime IDs it asks for.
+    pub fn from_start_game(vanilla: Vec<BlockState>, start_game: &StartGame) -> Result<Self> {
+        let mut states = vanilla;
+        states.extend(custom_block_states(&start_game.blocks)?);
+
+        Self::new(states, start_game.use_block_network_id_hashes.0)
+    }
+
+    /// Returns true if the runtime IDs are the hashes of the block states.
+    pub fn is_hashed(&self) -> bool {
+        self.hashed
+    }
+
+    /// Returns the runtime ID of the block state, if it is registered.
+    pub fn runtime_id(&self, state: &BlockState) -> Option<u32> {
+        self.runtime_ids.get(state).copied()
+    }
+
+    /// Returns the block state of the runtime ID, if it is registered.
+    pub fn state(&self, runtime_id: u32) -> Option<&BlockState> {
+        self.indices
+            .get(&runtime_id)
+            .map(|index| &self.states[*index])
+    }
+
+    /// Returns the block states sorted by the hash of their names, which is the order of their
+    /// runtime IDs if they are not hashed.
+    pub fn states(&self) -> &[BlockState] {
+        &self.states
+    }
+
+    /// Returns the checksum of the block states, which is sent as the server block state checksum
+    /// of the StartGame packet. It is the 64-bit FNV-1a hash of the canonical NBT of the states in
+    /// the order of the registry. A client whose block states do not match the checksum refuses to
+    /// join, while a checksum of zero makes it skip the verification.
+    pub fn checksum(&self) -> u64 {
+        self.states.iter().fold(FNV64_OFFSET_BASIS, |hash, state| {
+            fnv1a_64(hash, &state.canonical_nbt())
+        })
+    }
+
+    /// Returns the number of registered block states.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns true if no block states are registered.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+/// Returns the block states of the custom blocks of the StartGame packet. A custom block has a
+/// state for every permutation of the values of its properties, with the values of the first
+/// property changing the slowest.
+pub fn custom_block_states(blocks: &Slice<BlockEntry>) -> Result<Vec<BlockState>> {
+    let mut states = Vec::new();
+    for block in &blocks.0 {
+        let name = &block.name.0;
+        let mut permutations = vec![BlockState::new(name.as_str())];
+
+        let properties = match block.properties.get("properties") {
+            Some(NBTTag::List(properties)) => properties.as_slice(),
+            _ => &[],
+        };
+
+        for property in properties {
+            let property = match property {
+                NBTTag::Compound(property) => property,
+                _ => {
+                    return Err(Error::InvalidBlockState(format!(
+                        "property of {} is not a compound",
+                        name
+                    )))
+                }
+            };
+
+            let property_name = match property.get("name") {
+                Some(NBTTag::String(property_name)) => property_name,
+                _ => {
+                    return Err(Error::InvalidBlockState(format!(
+                        "property of {} without a name",
+                        name
+                    )))
+                }
+            };
+            let values = match property.get("enum") {
+                Some(NBTTag::List(values)) => values,
+                _ => {
+                    return Err(Error::InvalidBlockState(format!(
+                        "property {} without values",
+                        property_name
+                    )))
+                }
+            };
+            let values = values.iter().map(state_value).collect::<Result<Vec<_>>>()?;
+
+            permutations = permutations
+                .into_iter()
+                .flat_map(|state| {
+                    values
+                        .iter()
+                        .map(move |value| state.clone().with(property_name.as_str(), value.clone()))
+                })
+                .collect();
+        }
+
+        states.extend(permutations);
+    }
+
+    Ok(states)
+}
+
+/// Converts a tag to the value of a block state property.
+fn state_value(tag: &NBTTag) -> Result<BlockStateValue> {
+    match tag {
+        NBTTag::Byte(value) => Ok(BlockStateValue::Byte(*value as u8)),
+        NBTTag::Int(value) => Ok(BlockStateValue::Int(*value)),
+        NBTTag::String(value) => Ok(BlockStateValue::String(value.clone())),
+        tag => Err(Error::InvalidBlockState(format!(
+            "invalid property value {:?}",
+            tag
+        ))),
+    }
+}
+
+/// Writes a string prefixed with its length as a little endian u16, as in little endian NBT.
+fn write_string(buf: &mut Vec<u8>, value: &str) {
+    buf.write_u16::<LE>(value.len() as u16).unwrap();
+    buf.extend_from_slice(value.as_bytes());
+}
+
+const FNV32_OFFSET_BASIS: u32 = 0x811c9dc5;
+const FNV32_PRIME: u32 = 0x01000193;
+const FNV64_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV64_PRIME: u64 = 0x00000100000001b3;
+
+/// Returns the 32-bit FNV-1a hash of the data.
+fn fnv1a_32(data: &[u8]) -> u32 {
+    data.iter().fold(FNV32_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(FNV32_PRIME)
+    })
+}
+
+/// Continues the 64-bit FNV-1a hash with the data.
+fn fnv1a_64(hash: u64, data: &[u8]) -> u64 {
+    data.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV64_PRIME)
+    })
+}
+
+/// Returns the 64-bit FNV-1 hash of the data.
+fn fnv1_64(data: &[u8]) -> u64 {
+    data.iter().fold(FNV64_OFFSET_BASIS, |hash, byte| {
+        hash.wrapping_mul(FNV64_PRIME) ^ *byte as u64
+    })
+}
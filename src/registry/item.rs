@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use binary::datatypes::{Bool, I16};
+use byteorder::LE;
+use nbt::{NBTCompound, NBTTag};
+
+use crate::{
+    data::{
+        item::{ItemComponentEntry, ItemEntry},
+        VarString,
+    },
+    error::{Error, Result},
+    packet::play::{ItemComponent, StartGame},
+};
+
+/// This is the name of the shield, the only item whose stacks carry a blocking tick.
+pub const SHIELD: &str = "minecraft:shield";
+
+/// ItemDefinition is an item along with the runtime ID it is sent with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub runtime_id: i16,
+    /// ComponentBased is true if the item is defined by the components sent in the ItemComponent
+    /// packet.
+    pub component_based: bool,
+}
+
+/// CreativeCategory is the tab of the creative inventory a custom item is shown in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CreativeCategory {
+    Construction = 1,
+    Nature,
+    Equipment,
+    #[default]
+    Items,
+}
+
+/// CustomItem holds the properties of a custom item from which its components are generated.
+#[derive(Debug, Clone)]
+pub struct CustomItem {
+    pub name: String,
+    pub display_name: String,
+    /// Texture is the name of the texture of the item in the resource pack.
+    pub texture: String,
+    pub max_stack_size: i32,
+    /// HandEquipped is true if the item is held like a tool.
+    pub hand_equipped: bool,
+    pub creative_category: CreativeCategory,
+    pub creative_group: String,
+}
+
+impl CustomItem {
+    /// Creates and returns a new custom item, which stacks up to 64 and uses the texture of the
+    /// same name as the item.
+    pub fn new(name: impl Into<String>, display_name: impl Into<String>) -> Self {
+        let name = name.into();
+
+        Self {
+            texture: name.clone(),
+            name,
+            display_name: display_name.into(),
+            max_stack_size: 64,
+            hand_equipped: false,
+            creative_category: CreativeCategory::default(),
+            creative_group: String::new(),
+        }
+    }
+
+    /// Returns the entry of the ItemComponent packet defining the item with the runtime ID.
+    pub fn component_entry(&self, runtime_id: i16) -> ItemComponentEntry {
+        let properties = HashMap::from([
+            (
+                "minecraft:icon".into(),
+                NBTTag::Compound(HashMap::from([(
+                    "texture".into(),
+                    NBTTag::String(self.texture.clone()),
+                )])),
+            ),
+            ("max_stack_size".into(), NBTTag::Int(self.max_stack_size)),
+            (
+                "hand_equipped".into(),
+                NBTTag::Byte(self.hand_equipped as i8),
+            ),
+            (
+                "creative_category".into(),
+                NBTTag::Int(self.creative_category as i32),
+            ),
+            (
+                "creative_group".into(),
+                NBTTag::String(self.creative_group.clone()),
+            ),
+        ]);
+
+        let components = HashMap::from([
+            ("item_properties".into(), NBTTag::Compound(properties)),
+            (
+                "minecraft:display_name".into(),
+                NBTTag::Compound(HashMap::from([(
+                    "value".into(),
+                    NBTTag::String(self.display_name.clone()),
+                )])),
+            ),
+        ]);
+
+        let mut data = NBTCompound::new();
+        data.insert("components".into(), NBTTag::Compound(components));
+        data.insert("id".into(), NBTTag::Int(runtime_id as i32));
+        data.insert("name".into(), NBTTag::String(self.name.clone()));
+
+        ItemComponentEntry {
+            name: VarString::new(self.name.clone()),
+            data,
+        }
+    }
+}
+
+/// ItemRegistry maps the names of the items to the runtime IDs they are sent with, and back. It is
+/// built from the items of the StartGame packet, and holds the custom items registered on top of
+/// them so that the ItemComponent packet defining them can be generated.
+#[derive(Debug, Default)]
+pub struct ItemRegistry {
+    items: Vec<ItemDefinition>,
+    names: HashMap<String, usize>,
+    runtime_ids: HashMap<i16, usize>,
+    custom_items: Vec<CustomItem>,
+}
+
+impl ItemRegistry {
+    /// Creates and returns a new empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates and returns a new registry of the items of the StartGame packet.
+    pub fn from_start_game(start_game: &StartGame) -> Result<Self> {
+        let mut registry = Self::new();
+        for item in &start_game.items.0 {
+            registry.register(ItemDefinition {
+                name: item.name.0.clone(),
+                runtime_id: item.runtime_id.0,
+                component_based: item.component_based.0,
+            })?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Registers the item, failing if its name or runtime ID is already registered.
+    pub fn register(&mut self, item: ItemDefinition) -> Result<()> {
+        if self.names.contains_key(&item.name) {
+            return Err(Error::InvalidItem(format!("duplicate item {}", item.name)));
+        }
+        if let Some(index) = self.runtime_ids.get(&item.runtime_id) {
+            return Err(Error::InvalidItem(format!(
+                "runtime ID {} of {} is already used by {}",
+                item.runtime_id, item.name, self.items[*index].name
+            )));
+        }
+
+        self.names.insert(item.name.clone(), self.items.len());
+        self.runtime_ids.insert(item.runtime_id, self.items.len());
+        self.items.push(item);
+
+        Ok(())
+    }
+
+    /// Registers the custom item with the runtime ID following the highest registered one, and
+    /// returns its runtime ID.
+    pub fn register_custom(&mut self, item: CustomItem) -> Result<i16> {
+        let runtime_id = match self.runtime_ids.keys().max().copied() {
+            Some(i16::MAX) => {
+                return Err(Error::InvalidItem(format!(
+                    "no runtime ID left for {}",
+                    item.name
+                )))
+            }
+            Some(runtime_id) => runtime_id + 1,
+            None => 1,
+        };
+
+        self.register(ItemDefinition {
+            name: item.name.clone(),
+            runtime_id,
+            component_based: true,
+        })?;
+        self.custom_items.push(item);
+
+        Ok(runtime_id)
+    }
+
+    /// Returns the item with the name, if it is registered.
+    pub fn by_name(&self, name: &str) -> Option<&ItemDefinition> {
+        self.names.get(name).map(|index| &self.items[*index])
+    }
+
+    /// Returns the item with the runtime ID, if it is registered.
+    pub fn by_runtime_id(&self, runtime_id: i16) -> Option<&ItemDefinition> {
+        self.runtime_ids
+            .get(&runtime_id)
+            .map(|index| &self.items[*index])
+    }
+
+    /// Returns the runtime ID of the shield, if it is registered. The item stacks of the shield
+    /// must be encoded with a blocking tick.
+    pub fn shield_runtime_id(&self) -> Option<i32> {
+        self.by_name(SHIELD).map(|item| item.runtime_id as i32)
+    }
+
+    /// Returns the registered items, in the order they were registered in.
+    pub fn items(&self) -> &[ItemDefinition] {
+        &self.items
+    }
+
+    /// Returns the registered custom items.
+    pub fn custom_items(&self) -> &[CustomItem] {
+        &self.custom_items
+    }
+
+    /// Returns the number of registered items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if no items are registered.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the entries of the item table of the StartGame packet for the registered items, both
+    /// vanilla and custom, in the order they were registered in.
+    pub fn entries(&self) -> Vec<ItemEntry> {
+        self.items
+            .iter()
+            .map(|item| ItemEntry {
+                name: VarString::new(item.name.clone()),
+                runtime_id: I16::<LE>::new(item.runtime_id),
+                component_based: Bool::new(item.component_based),
+            })
+            .collect()
+    }
+
+    /// Returns the ItemComponent packet defining the registered custom items.
+    pub fn item_component(&self) -> ItemComponent {
+        let items = self
+            .custom_items
+            .iter()
+            .map(|item| {
+                let index = self.names[&item.name];
+                item.component_entry(self.items[index].runtime_id)
+            })
+            .collect::<Vec<_>>();
+
+        ItemComponent {
+            items: items.into(),
+        }
+    }
+}